@@ -1,19 +1,217 @@
-use std::path::PathBuf;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
 
 use anyhow::{Ok, Result};
-use chrono::Local;
+use chrono::{DateTime, Local, NaiveDate, SecondsFormat, TimeDelta, TimeZone};
 
+use crate::archive::{self, ArchiveEntry, ArchiveManifest, Retention};
 use crate::cli;
 use crate::config;
-use crate::config::{Config, PartialConfig};
+use crate::config::{ArchiveFormat, Config, PartialConfig};
 use crate::error::AppError;
+use crate::error::InternalError;
 use crate::error::SystemError;
 use crate::file_operations::FileOperations;
-use crate::message::Message;
+use crate::front_matter::{self, FieldValue, FrontMatter};
+use crate::index::{self, SearchIndex};
+use crate::journal::{Blob, FileChange, Journal, Operation};
+use crate::message::{
+    ArchivedVersion, ConfigValue, HistoryEntry, Message, NoteContent, NoteInfo, NotebookInfo,
+    RestoredNote, TagCount, TrashedNote,
+};
+use crate::resolve::{self, Resolved};
+use crate::search::{Matcher, SearchMatch, SearchOptions};
+use crate::tags::{self, TagFilter};
+use crate::template;
+use crate::terminal::TerminalOperations;
+use crate::trash::{Trash, TrashEntry};
 
 const RN_ROOT_DIR: &str = ".rn";
 const NOTEBOOK_DIR_NAME: &str = "notebook";
 const ARCHIVE_DIR_NAME: &str = "archive";
+const NOTEBOOKS_DIR_NAME: &str = "notebooks";
+const CURRENT_NOTEBOOK_FILE_NAME: &str = ".current_notebook";
+const DEFAULT_NOTEBOOK: &str = "default";
+const INDEX_FILE_NAME: &str = ".index.json";
+const TEMPLATES_DIR_NAME: &str = "templates";
+const JOURNAL_DIR_NAME: &str = "journal";
+const TRASH_DIR_NAME: &str = ".trash";
+const TRASH_FILE_NAME: &str = ".entries.json";
+const UNDO_DIR_NAME: &str = ".journal";
+const UNDO_FILE_NAME: &str = "journal.json";
+const BLOBS_DIR_NAME: &str = "blobs";
+const ARCHIVE_FILE_NAME: &str = ".versions.json";
+const STAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M";
+
+/// Formats `text` to be added to a note, optionally with a line holding the
+/// current time in front. Non empty entries always end with a line break.
+fn format_entry(text: &str, stamp: bool) -> String {
+    let mut entry = if stamp {
+        format!("[{}]\n", Local::now().format(STAMP_FORMAT))
+    } else {
+        String::new()
+    };
+    entry.push_str(text);
+    if !entry.is_empty() && !entry.ends_with('\n') {
+        entry.push('\n');
+    }
+    entry
+}
+
+/// Lines of `content` selected by `--lines`, `--head` or `--tail`, line
+/// endings are kept.
+fn select_lines(content: &str, args: &cli::ShowArgs) -> String {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let count = lines.len();
+    let (start, end) = if let Some(range) = args.lines {
+        (
+            range.start.map_or(0, |start| start.saturating_sub(1)),
+            range.end.unwrap_or(count),
+        )
+    } else if let Some(head) = args.head {
+        (0, head)
+    } else if let Some(tail) = args.tail {
+        (count.saturating_sub(tail), count)
+    } else {
+        (0, count)
+    };
+    lines
+        .get(start.min(count)..end.min(count))
+        .unwrap_or_default()
+        .concat()
+}
+
+/// Converts seconds since the unix epoch to local time.
+fn local_time(time: i64) -> DateTime<Local> {
+    Local.timestamp_opt(time, 0).single().unwrap_or_default()
+}
+
+/// Formats `date`, unlike `to_string` an invalid `format` is an error
+/// instead of a panic.
+fn format_date(date: NaiveDate, format: &str) -> Result<String> {
+    use std::fmt::Write;
+    let mut formatted = String::new();
+    write!(formatted, "{}", date.format(format))
+        .map_err(|_| AppError::InvalidDateFormat(format.to_owned()))?;
+    Ok(formatted)
+}
+
+/// Parses `input` as ISO 8601 date or in the given `format`.
+fn parse_date(input: &str, format: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(input, DATE_FORMAT)
+        .or_else(|_| NaiveDate::parse_from_str(input, format))
+        .map_err(|_| AppError::InvalidDate(input.to_owned(), Some(format.to_owned())).into())
+}
+
+/// Parses a `YYYY-MM-DD` date of a command unrelated to journal notes.
+fn parse_iso_date(input: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(input, DATE_FORMAT)
+        .map_err(|_| AppError::InvalidDate(input.to_owned(), None).into())
+}
+
+/// Name for a note `name` is moved or copied to, a `new_name` ending with
+/// `/` is the folder the note is put in under its current name.
+fn target_name(name: &str, new_name: &str) -> String {
+    let Some(folder) = new_name.strip_suffix('/') else {
+        return new_name.to_owned();
+    };
+    let base_name = name.rsplit('/').next().unwrap_or(name);
+    if folder.is_empty() {
+        base_name.to_owned()
+    } else {
+        format!("{folder}/{base_name}")
+    }
+}
+
+/// Name under which a command is kept in the journal, commands which don't
+/// change notes aren't journaled.
+fn journal_label(subcommand: &cli::Subcommand) -> Option<&'static str> {
+    match subcommand {
+        cli::Subcommand::New(_) => Some("new"),
+        cli::Subcommand::Append(_) => Some("append"),
+        cli::Subcommand::Move(_) => Some("mv"),
+        cli::Subcommand::Copy(_) => Some("cp"),
+        cli::Subcommand::Remove(_) => Some("rm"),
+        cli::Subcommand::Today(_) => Some("today"),
+        cli::Subcommand::Yesterday(_) => Some("yesterday"),
+        cli::Subcommand::Day(_) => Some("day"),
+        cli::Subcommand::Snapshot(_) => Some("snapshot"),
+        cli::Subcommand::Meta(args) => match args.subcommand {
+            cli::MetaSubcommand::Set(_) => Some("meta set"),
+            cli::MetaSubcommand::Unset(_) => Some("meta unset"),
+            cli::MetaSubcommand::Get(_) => None,
+        },
+        cli::Subcommand::Tag(args) => match args.subcommand {
+            cli::TagSubcommand::Add(_) => Some("tag add"),
+            cli::TagSubcommand::Remove(_) => Some("tag remove"),
+        },
+        cli::Subcommand::Archive(args) => match args.subcommand {
+            cli::ArchiveSubcommand::Save(_) => Some("archive save"),
+            cli::ArchiveSubcommand::Restore(_) => Some("archive restore"),
+            cli::ArchiveSubcommand::Move(_) => Some("archive mv"),
+            cli::ArchiveSubcommand::Remove(_) => Some("archive rm"),
+            cli::ArchiveSubcommand::Prune(_) => Some("archive prune"),
+            cli::ArchiveSubcommand::Compact => Some("archive compact"),
+            _ => None,
+        },
+        cli::Subcommand::Config(args) => match args.subcommand {
+            cli::ConfigSubcommand::Generate(_) => Some("config generate"),
+            _ => None,
+        },
+        cli::Subcommand::Template(args) => match args.subcommand {
+            cli::TemplateSubcommand::New(_) => Some("template new"),
+            cli::TemplateSubcommand::Remove(_) => Some("template rm"),
+            _ => None,
+        },
+        cli::Subcommand::Trash(args) => match args.subcommand {
+            cli::TrashSubcommand::Restore(_) => Some("trash restore"),
+            cli::TrashSubcommand::Empty => Some("trash empty"),
+            cli::TrashSubcommand::Purge(_) => Some("trash purge"),
+            cli::TrashSubcommand::List => None,
+        },
+        _ => None,
+    }
+}
+
+/// Current time as written to the `created` and `updated` front matter fields.
+fn front_matter_time() -> String {
+    Local::now().to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+/// Replaces a leading `~` with the home directory of the current user.
+fn expand_home(path: &Path) -> Result<PathBuf> {
+    let Some(rest) = path.strip_prefix("~").ok() else {
+        return Ok(path.to_path_buf());
+    };
+    let Some(mut home_dir) = std::env::home_dir() else {
+        return Err(SystemError::NoHomeDir.into());
+    };
+    home_dir.push(rest);
+    Ok(home_dir)
+}
+
+/// Notebook names are single path components, which also rules out `.` and
+/// `..`.
+fn check_notebook_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.contains('/') || name.starts_with('.') {
+        return Err(AppError::InvalidNotebookName(name.to_owned()).into());
+    }
+    Ok(())
+}
+
+/// Note names are `/` separated paths relative to the notebook directory.
+fn check_note_name(name: &str) -> Result<()> {
+    let valid = name
+        .split('/')
+        .all(|component| !component.is_empty() && !component.starts_with('.'));
+    if !valid {
+        return Err(AppError::InvalidNoteName(name.to_owned()).into());
+    }
+    Ok(())
+}
 
 #[derive(Clone, Copy)]
 enum NoteType {
@@ -21,48 +219,126 @@ enum NoteType {
     Archived,
 }
 
-pub struct App<FS: FileOperations> {
+/// Identifies a note in search results and the search index.
+fn note_label(name: &str, note_type: NoteType) -> String {
+    match note_type {
+        NoteType::Active => name.to_owned(),
+        NoteType::Archived => format!("{ARCHIVE_DIR_NAME}:{name}"),
+    }
+}
+
+pub struct App<FS: FileOperations, TO: TerminalOperations> {
     pub config: config::Config,
     pub rn_root_dir: PathBuf,
+    pub notebook: String,
+    pub exact: bool,
+    /// Notes are only paged for text output which isn't suppressed.
+    pub format: cli::OutputFormat,
+    pub quiet: bool,
     fs: FS,
+    terminal: TO,
+    /// Files changed by the running command, if it's journaled.
+    recording: Option<Vec<FileChange>>,
 }
 
-impl<FS: FileOperations> App<FS> {
-    pub fn new(config: config::Config, fs: FS) -> Result<Self> {
-        let Some(mut rn_root_dir) = std::env::home_dir() else {
-            return Err(SystemError::NoHomeDir.into());
+impl<FS: FileOperations, TO: TerminalOperations> App<FS, TO> {
+    pub fn new(config: config::Config, fs: FS, terminal: TO) -> Result<Self> {
+        let rn_root_dir = if let Some(root_dir) = &config.root_dir {
+            expand_home(root_dir)?
+        } else {
+            let Some(mut rn_root_dir) = std::env::home_dir() else {
+                return Err(SystemError::NoHomeDir.into());
+            };
+            rn_root_dir.push(RN_ROOT_DIR);
+            rn_root_dir
         };
-        rn_root_dir.push(RN_ROOT_DIR);
         Ok(Self {
             config,
             rn_root_dir,
+            notebook: DEFAULT_NOTEBOOK.to_owned(),
+            exact: false,
+            format: cli::OutputFormat::Text,
+            quiet: false,
             fs,
+            terminal,
+            recording: None,
         })
     }
 
-    fn notebook_dir(&self) -> PathBuf {
-        let mut notebook_dir = self.rn_root_dir.clone();
-        notebook_dir.push(NOTEBOOK_DIR_NAME);
-        notebook_dir
+    fn notebooks_dir(&self) -> PathBuf {
+        let mut notebooks_dir = self.rn_root_dir.clone();
+        notebooks_dir.push(NOTEBOOKS_DIR_NAME);
+        notebooks_dir
     }
 
-    fn archive_dir(&self) -> PathBuf {
-        let mut archive_dir = self.rn_root_dir.clone();
-        archive_dir.push(ARCHIVE_DIR_NAME);
-        archive_dir
+    fn current_notebook_file(&self) -> PathBuf {
+        let mut current_notebook_file = self.rn_root_dir.clone();
+        current_notebook_file.push(CURRENT_NOTEBOOK_FILE_NAME);
+        current_notebook_file
     }
 
-    fn get_dir_path(&self, note_type: NoteType) -> PathBuf {
+    /// The default notebook lives directly in the rn root directory, so
+    /// notes created before notebooks existed stay where they are.
+    fn notebook_root_dir(&self, notebook: &str) -> PathBuf {
+        if notebook == DEFAULT_NOTEBOOK {
+            return self.rn_root_dir.clone();
+        }
+        let mut notebook_root_dir = self.notebooks_dir();
+        notebook_root_dir.push(notebook);
+        notebook_root_dir
+    }
+
+    fn get_notebook_dir_path(&self, notebook: &str, note_type: NoteType) -> PathBuf {
+        let mut path = self.notebook_root_dir(notebook);
         match note_type {
-            NoteType::Active => self.notebook_dir(),
-            NoteType::Archived => self.archive_dir(),
+            NoteType::Active => path.push(NOTEBOOK_DIR_NAME),
+            NoteType::Archived => path.push(ARCHIVE_DIR_NAME),
         }
+        path
+    }
+
+    fn get_dir_path(&self, note_type: NoteType) -> PathBuf {
+        self.get_notebook_dir_path(&self.notebook, note_type)
     }
 
-    fn get_note_path(&self, name: &str, note_type: NoteType) -> PathBuf {
+    fn get_note_path(&self, name: &str, note_type: NoteType) -> Result<PathBuf> {
+        check_note_name(name)?;
         let mut path = self.get_dir_path(note_type);
         path.push(name);
-        path
+        Ok(path)
+    }
+
+    /// Creates the folders of a nested note if they don't exist yet.
+    fn create_parent_dirs(&mut self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            if !self.fs.exists(parent)? {
+                self.fs.create_dir(parent)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Deletes a note and every folder that is empty afterwards.
+    fn delete_note_file(&mut self, path: &Path, note_type: NoteType) -> Result<()> {
+        self.record_change(path)?;
+        self.fs.delete_file(path)?;
+        self.delete_empty_parent_dirs(path, note_type)
+    }
+
+    /// Deletes the folders above the removed note at `path` which became
+    /// empty.
+    fn delete_empty_parent_dirs(&mut self, path: &Path, note_type: NoteType) -> Result<()> {
+        let dir = self.get_dir_path(note_type);
+        for parent in path.ancestors().skip(1) {
+            if parent == dir || !parent.starts_with(&dir) {
+                break;
+            }
+            // folders with hidden files aren't empty either
+            if !self.fs.delete_dir(parent)? {
+                break;
+            }
+        }
+        Ok(())
     }
 
     fn check_dir_structure(&mut self) -> Result<()> {
@@ -70,203 +346,3180 @@ impl<FS: FileOperations> App<FS> {
         if !self.fs.exists(rn_root_dir)? {
             self.fs.create_dir(rn_root_dir)?;
         }
-        let active_dir = self.get_dir_path(NoteType::Active);
+        self.create_notebook_dirs(DEFAULT_NOTEBOOK)
+    }
+
+    fn create_notebook_dirs(&mut self, notebook: &str) -> Result<()> {
+        let active_dir = self.get_notebook_dir_path(notebook, NoteType::Active);
         if !self.fs.exists(&active_dir)? {
             self.fs.create_dir(&active_dir)?;
         }
-        let archive_dir = self.get_dir_path(NoteType::Archived);
+        let archive_dir = self.get_notebook_dir_path(notebook, NoteType::Archived);
         if !self.fs.exists(&archive_dir)? {
             self.fs.create_dir(&archive_dir)?;
         }
         Ok(())
     }
 
-    fn open_note(&mut self, name: String, note_type: NoteType) -> Result<Message> {
-        let path = self.get_note_path(name.as_str(), note_type);
+    /// Every notebook name given by the user passes through here, so it's
+    /// checked before it's joined onto the notebooks directory.
+    fn notebook_exists(&self, notebook: &str) -> Result<bool> {
+        check_notebook_name(notebook)?;
+        if notebook == DEFAULT_NOTEBOOK {
+            return Ok(true);
+        }
+        self.fs.exists(&self.notebook_root_dir(notebook))
+    }
+
+    fn current_notebook(&self) -> Result<String> {
+        let path = self.current_notebook_file();
         if !self.fs.exists(&path)? {
+            return Ok(DEFAULT_NOTEBOOK.to_owned());
+        }
+        let notebook = self.fs.read_file(&path)?.trim().to_owned();
+        if notebook.is_empty() {
+            return Ok(DEFAULT_NOTEBOOK.to_owned());
+        }
+        Ok(notebook)
+    }
+
+    fn select_notebook(&mut self, notebook: Option<String>) -> Result<()> {
+        let notebook = match notebook {
+            Some(notebook) => notebook,
+            None => self.current_notebook()?,
+        };
+        if !self.notebook_exists(&notebook)? {
+            return Err(AppError::NotebookNotFound(notebook).into());
+        }
+        self.notebook = notebook;
+        Ok(())
+    }
+
+    /// Resolves a name given by the user to an existing note, accepting
+    /// unique prefixes and fuzzy matches unless `--exact` is used.
+    fn resolve_note(&self, name: String, note_type: NoteType) -> Result<String> {
+        let names = self.fs.get_files_recursive(&self.get_dir_path(note_type))?;
+        if self.exact {
+            if names.contains(&name) {
+                return Ok(name);
+            }
             return Err(AppError::NotFound(name).into());
         }
-        self.fs.open_file(&self.config.editor, &path)?;
+        match resolve::resolve(&name, &names) {
+            Resolved::Found(resolved_name) => Ok(resolved_name),
+            Resolved::Ambiguous(suggestions) => Err(AppError::Ambiguous(name, suggestions).into()),
+            Resolved::NotFound => Err(AppError::NotFound(name).into()),
+        }
+    }
+
+    /// Resolves the given name, or lets the user pick notes if there is none.
+    fn resolve_or_pick_notes(
+        &mut self,
+        name: Option<String>,
+        note_type: NoteType,
+        multi: bool,
+    ) -> Result<Vec<String>> {
+        if let Some(name) = name {
+            return Ok(vec![self.resolve_note(name, note_type)?]);
+        }
+        let dir = self.get_dir_path(note_type);
+        let mut names = self.fs.get_files_recursive(&dir)?;
+        names.sort();
+        let fs = &self.fs;
+        // previews of notes which can't be read stay empty
+        self.terminal.pick(&names, multi, |name| {
+            fs.read_bytes(&dir.join(name))
+                .and_then(archive::decode)
+                .unwrap_or_default()
+        })
+    }
+
+    fn open_note(&mut self, name: Option<String>, note_type: NoteType) -> Result<Message> {
+        let Some(name) = self.resolve_or_pick_notes(name, note_type, false)?.pop() else {
+            return Ok(Message::Empty);
+        };
+        let path = self.get_note_path(&name, note_type)?;
+        let is_active = matches!(note_type, NoteType::Active);
+        if is_active && self.config.snapshot_on_open && self.is_changed_since_snapshot(&name)? {
+            self.archive_note(&name, true)?;
+        }
+        // compressed archived notes are edited as text and compressed again
+        let stored = self.fs.read_bytes(&path)?;
+        let is_compressed = archive::is_compressed(&stored);
+        let content = archive::decode(stored)?;
+        if is_compressed {
+            self.fs.write_file(&path, &content)?;
+        }
+        self.edit_note(&path, &content)?;
+        if is_compressed && self.fs.exists(&path)? {
+            let content = archive::compress(&self.fs.read_bytes(&path)?)?;
+            self.fs.write_bytes(&path, &content)?;
+        }
+        self.update_index(&[(name.as_str(), note_type)])?;
         Ok(Message::Empty)
     }
 
-    fn get_config_values<T: AsRef<str>>(&self, value_names: &[T]) -> Result<Vec<(String, String)>> {
-        let mut config_values: Vec<(String, String)> = Vec::new();
-        let config_file_path = config::config_file()?;
-        let config_exists = self.fs.exists(&config_file_path)?;
-        if !config_exists {
-            return Ok(config_values);
+    /// Opens the note at `path` in the editor, `updated` is set in its front
+    /// matter if `content` was changed.
+    fn edit_note(&mut self, path: &Path, content: &str) -> Result<()> {
+        self.fs.open_file(&self.config.editor, path)?;
+        if !self.fs.exists(path)? {
+            return Ok(());
         }
-        let config = PartialConfig::from_config_file(&self.fs)?;
-        for value_name in value_names {
-            let value_name = value_name.as_ref();
-            let value = match value_name {
-                config::value_names::EDITOR => config.editor.as_ref(),
-                _ => continue,
-            };
-            if let Some(value) = value {
-                config_values.push((value_name.to_owned(), value.to_owned()));
+        let edited = self.fs.read_file(path)?;
+        if edited != content {
+            if let Some(edited) = front_matter::touch(&edited, &front_matter_time(), false) {
+                self.fs.write_file(path, &edited)?;
             }
         }
-        Ok(config_values)
+        Ok(())
     }
 
-    #[allow(clippy::needless_pass_by_value)]
-    fn handle_new(&mut self, args: cli::NewArgs) -> Result<Message> {
-        let name = args.name;
-        let path = self.get_note_path(&name, NoteType::Active);
-        if self.fs.exists(&path)? {
-            return Err(AppError::AlreadyExists(name).into());
+    /// Paths, sizes and modification times of the notes `names`.
+    fn note_infos(&self, names: Vec<String>, note_type: NoteType) -> Result<Vec<NoteInfo>> {
+        names
+            .into_iter()
+            .map(|name| {
+                let path = self.get_note_path(&name, note_type)?;
+                let metadata = self.fs.metadata(&path)?;
+                Ok(NoteInfo {
+                    name,
+                    path,
+                    size: metadata.size,
+                    created: metadata.created,
+                    modified: metadata.modified,
+                    lines: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Moves a note to a new name, optionally into another notebook. A new
+    /// name ending with `/` keeps the name and only changes the folder.
+    fn move_note(&mut self, args: cli::MoveArgs, note_type: NoteType) -> Result<Message> {
+        let notebook = args.to_notebook.unwrap_or_else(|| self.notebook.clone());
+        check_notebook_name(&notebook)?;
+        if !self.notebook_exists(&notebook)? {
+            return Err(AppError::NotebookNotFound(notebook).into());
         }
-        self.fs.create_file(&path)?;
-        Ok(Message::CreatedNote)
+        let name = self.resolve_note(args.name, note_type)?;
+        let new_name = target_name(&name, &args.new_name);
+        check_note_name(&new_name)?;
+        let path = self.get_note_path(&name, note_type)?;
+        let new_path = self
+            .get_notebook_dir_path(&notebook, note_type)
+            .join(&new_name);
+        if path == new_path {
+            return Ok(Message::MovedNote {
+                from: name,
+                to: new_name,
+            });
+        }
+        if self.fs.exists(&new_path)? && !args.force {
+            return Err(AppError::AlreadyExists(new_name).into());
+        }
+        self.create_parent_dirs(&new_path)?;
+        self.record_change(&path)?;
+        self.record_change(&new_path)?;
+        self.fs.rename(&path, &new_path)?;
+        self.delete_empty_parent_dirs(&path, note_type)?;
+        if matches!(note_type, NoteType::Archived) {
+            self.move_archive_entry(&name, &notebook, &new_name)?;
+        }
+        // notes moved into another notebook are picked up by its index on
+        // the next search
+        if notebook == self.notebook {
+            self.update_index(&[(&name, note_type), (&new_name, note_type)])?;
+        } else {
+            self.update_index(&[(&name, note_type)])?;
+        }
+        let new_name = if notebook == self.notebook {
+            new_name
+        } else {
+            format!("{notebook}:{new_name}")
+        };
+        Ok(Message::MovedNote {
+            from: name,
+            to: new_name,
+        })
     }
 
-    #[allow(clippy::needless_pass_by_value)]
-    fn handle_remove(&mut self, args: cli::RemoveArgs) -> Result<Message> {
-        let name = args.name;
-        let path = self.get_note_path(&name, NoteType::Active);
-        if !self.fs.exists(&path)? {
-            return Err(AppError::NotFound(name).into());
+    fn show_notes(&mut self, args: &cli::ShowArgs, note_type: NoteType) -> Result<Message> {
+        let mut notes = Vec::new();
+        for name in &args.names {
+            let name = self.resolve_note(name.clone(), note_type)?;
+            let content = self.read_note_file(&self.get_note_path(&name, note_type)?)?;
+            notes.push(NoteContent {
+                content: select_lines(&content, args),
+                name,
+            });
         }
-        self.fs.delete_file(&path)?;
-        Ok(Message::DeletedNote)
+        let message = Message::NoteContents { notes };
+        let is_text = self.format == cli::OutputFormat::Text && !self.quiet;
+        if is_text
+            && self
+                .terminal
+                .page(&self.config.pager, &message.to_string())?
+        {
+            return Ok(Message::Empty);
+        }
+        Ok(message)
     }
 
-    fn handle_list(&self) -> Result<Message> {
-        let notes = self.fs.get_files(&self.get_dir_path(NoteType::Active))?;
-        Ok(Message::Notebook(notes))
+    fn templates_dir(&self) -> PathBuf {
+        self.rn_root_dir.join(TEMPLATES_DIR_NAME)
     }
 
-    fn handle_open(&mut self, args: cli::OpenArgs) -> Result<Message> {
+    fn get_template_path(&self, name: &str) -> Result<PathBuf> {
+        check_note_name(name)?;
+        Ok(self.templates_dir().join(name))
+    }
+
+    /// Fills the template for the note `name`, `custom_variables` take
+    /// precedence over the built-in ones.
+    fn render_template(
+        &self,
+        template_name: &str,
+        name: &str,
+        custom_variables: Vec<(String, String)>,
+    ) -> Result<String> {
+        let path = self.get_template_path(template_name)?;
+        if !self.fs.exists(&path)? {
+            return Err(AppError::TemplateNotFound(template_name.to_owned()).into());
+        }
+        let now = Local::now();
+        let mut variables = BTreeMap::from([
+            (String::from("name"), name.to_owned()),
+            (String::from("date"), now.format(DATE_FORMAT).to_string()),
+            (String::from("time"), now.format(TIME_FORMAT).to_string()),
+            (String::from("notebook"), self.notebook.clone()),
+        ]);
+        variables.extend(custom_variables);
+        template::render(&self.fs.read_file(&path)?, &variables)
+    }
+
+    /// Opens the journal note of `date`, it's created first if it doesn't
+    /// exist yet.
+    fn open_journal_note(&mut self, date: NaiveDate, args: cli::JournalArgs) -> Result<Message> {
+        let name = format!(
+            "{JOURNAL_DIR_NAME}/{}",
+            format_date(date, &self.config.journal_date_format)?
+        );
+        let path = self.get_note_path(&name, NoteType::Active)?;
+        if !self.fs.exists(&path)? {
+            let template_name = args
+                .template
+                .or_else(|| self.config.journal_template.clone());
+            let content = match template_name {
+                Some(template_name) => {
+                    let date_variable = (String::from("date"), format_date(date, DATE_FORMAT)?);
+                    self.render_template(&template_name, &name, vec![date_variable])?
+                }
+                None => String::new(),
+            };
+            let content =
+                front_matter::touch(&content, &front_matter_time(), true).unwrap_or(content);
+            self.create_parent_dirs(&path)?;
+            self.record_change(&path)?;
+            self.fs.create_file(&path)?;
+            if !content.is_empty() {
+                self.fs.append_file(&path, &content)?;
+            }
+        }
         if let Some(editor) = args.editor {
             self.config.editor = editor;
         }
-        self.open_note(args.name, NoteType::Active)
+        let content = self.fs.read_file(&path)?;
+        self.edit_note(&path, &content)?;
+        self.update_index(&[(&name, NoteType::Active)])?;
+        Ok(Message::Empty)
     }
 
-    #[allow(clippy::unused_self)]
-    #[allow(clippy::needless_pass_by_value)]
-    fn handle_completions(&self, args: cli::CompletionArgs) -> Result<Message> {
-        let script = match args.shell {
-            cli::Shell::Zsh => include_str!("../completions/_rn").to_owned(),
+    /// Copies a note into the archive and returns its archived name. The note
+    /// is deleted unless `keep` is set.
+    fn archive_note(&mut self, name: &str, keep: bool) -> Result<String> {
+        let active_path = self.get_note_path(name, NoteType::Active)?;
+        let now = Local::now();
+        let time_stamp = now.format(archive::TIME_STAMP_FORMAT).to_string();
+        let archived_name = format!("{name}_{time_stamp}");
+        let archived_path = self.get_note_path(&archived_name, NoteType::Archived)?;
+        if self.fs.exists(&archived_path)? {
+            return Err(AppError::ArchiveAlreadyExists(archived_name).into());
+        }
+        self.create_parent_dirs(&archived_path)?;
+        self.record_change(&active_path)?;
+        self.record_change(&archived_path)?;
+        if matches!(self.config.archive_format, ArchiveFormat::Gzip) {
+            let content = archive::compress(&self.fs.read_bytes(&active_path)?)?;
+            self.fs.write_bytes(&archived_path, &content)?;
+        } else {
+            self.fs.copy(&active_path, &archived_path)?;
+        }
+        if !keep {
+            self.delete_note_file(&active_path, NoteType::Active)?;
+        }
+        let mut manifest = self.load_archive_manifest(&self.notebook)?;
+        manifest.entries.insert(
+            archived_name.clone(),
+            ArchiveEntry {
+                original: name.to_owned(),
+                archived: now.timestamp(),
+            },
+        );
+        self.save_archive_manifest(&self.notebook.clone(), &manifest)?;
+        self.update_index(&[
+            (name, NoteType::Active),
+            (&archived_name, NoteType::Archived),
+        ])?;
+        Ok(archived_name)
+    }
+
+    /// Whether the note `name` differs from its latest archived version, or
+    /// was never archived.
+    fn is_changed_since_snapshot(&self, name: &str) -> Result<bool> {
+        let names = self
+            .fs
+            .get_files_recursive(&self.get_dir_path(NoteType::Archived))?;
+        let manifest = self.load_archive_manifest(&self.notebook)?;
+        let versions = manifest.versions(name, &names);
+        let Some((latest, _)) = versions.last() else {
+            return Ok(true);
         };
-        Ok(Message::CompletionScript(script))
+        let content = self.read_note_file(&self.get_note_path(name, NoteType::Active)?)?;
+        let snapshot = self.read_note_file(&self.get_note_path(latest, NoteType::Archived)?)?;
+        Ok(content != snapshot)
     }
 
-    fn handle_config(&mut self, args: cli::ConfigArgs) -> Result<Message> {
-        match args.subcommand {
-            cli::ConfigSubcommand::Generate(args) => self.handle_config_generate(args),
-            cli::ConfigSubcommand::Get(args) => self.handle_config_get(args),
-            cli::ConfigSubcommand::List => self.handle_config_list(),
+    /// Retention rules of the config, the maximum age is relative to now.
+    fn retention(&self) -> Result<Retention> {
+        let cutoff = match &self.config.archive_max_age {
+            Some(max_age) => {
+                let age = cli::age(max_age).map_err(|reason| {
+                    AppError::InvalidConfigValue(
+                        config::value_names::ARCHIVE_MAX_AGE.to_owned(),
+                        reason,
+                    )
+                })?;
+                let age = TimeDelta::from_std(age).unwrap_or(TimeDelta::MAX);
+                Some(
+                    Local::now()
+                        .checked_sub_signed(age)
+                        .map_or(i64::MIN, |cutoff| cutoff.timestamp()),
+                )
+            }
+            None => None,
+        };
+        Ok(Retention {
+            keep_last: self.config.archive_keep_last,
+            keep_daily: self.config.archive_keep_daily,
+            keep_weekly: self.config.archive_keep_weekly,
+            keep_monthly: self.config.archive_keep_monthly,
+            cutoff,
+        })
+    }
+
+    fn archive_manifest_file(&self, notebook: &str) -> PathBuf {
+        self.get_notebook_dir_path(notebook, NoteType::Archived)
+            .join(ARCHIVE_FILE_NAME)
+    }
+
+    fn load_archive_manifest(&self, notebook: &str) -> Result<ArchiveManifest> {
+        let manifest_file = self.archive_manifest_file(notebook);
+        if !self.fs.exists(&manifest_file)? {
+            return Ok(ArchiveManifest::default());
         }
+        serde_json::from_str(&self.fs.read_file(&manifest_file)?)
+            .map_err(|e| InternalError(e.into()).into())
     }
 
-    #[allow(clippy::needless_pass_by_value)]
-    fn handle_config_generate(&mut self, args: cli::ConfigGenerateArgs) -> Result<Message> {
-        let config_file_path = config::config_file()?;
-        let config_exists = self.fs.exists(&config_file_path)?;
-        if config_exists && !args.force {
-            return Err(AppError::ConfigAlreadyExists(config_file_path).into());
+    fn save_archive_manifest(&mut self, notebook: &str, manifest: &ArchiveManifest) -> Result<()> {
+        let json = serde_json::to_string_pretty(manifest).map_err(|e| InternalError(e.into()))?;
+        let manifest_file = self.archive_manifest_file(notebook);
+        self.record_change(&manifest_file)?;
+        self.fs.write_file(&manifest_file, &json)
+    }
+
+    /// Moves the metadata of a renamed archived note along with it.
+    fn move_archive_entry(&mut self, name: &str, notebook: &str, new_name: &str) -> Result<()> {
+        let mut manifest = self.load_archive_manifest(&self.notebook)?;
+        let entry = manifest
+            .entries
+            .remove(name)
+            .unwrap_or_else(|| manifest.entry(name));
+        if notebook == self.notebook {
+            manifest.entries.insert(new_name.to_owned(), entry);
+        } else {
+            let mut other_manifest = self.load_archive_manifest(notebook)?;
+            other_manifest.entries.insert(new_name.to_owned(), entry);
+            self.save_archive_manifest(notebook, &other_manifest)?;
         }
-        let config = Config::default();
-        let config_string = config.to_toml()?;
-        #[allow(clippy::unwrap_used)] // `config_file_path` is never empty
-        let dir_path = config_file_path.parent().unwrap();
-        self.fs.create_dir(dir_path)?;
-        self.fs.write_file(&config_file_path, &config_string)?;
-        Ok(Message::GeneratedConfig(config_file_path))
+        self.save_archive_manifest(&self.notebook.clone(), &manifest)
     }
 
-    #[allow(clippy::needless_pass_by_value)]
-    fn handle_config_get(&self, args: cli::ConfigGetArgs) -> Result<Message> {
-        Ok(Message::ConfigValues(
-            self.get_config_values(&args.value_names)?,
-        ))
+    /// Original name of the archived note `name` and all its archived
+    /// versions, oldest first.
+    fn archived_versions(&self, name: String) -> Result<(String, Vec<(String, ArchiveEntry)>)> {
+        let names = self
+            .fs
+            .get_files_recursive(&self.get_dir_path(NoteType::Archived))?;
+        let manifest = self.load_archive_manifest(&self.notebook)?;
+        let originals: Vec<String> = names
+            .iter()
+            .map(|archived_name| manifest.entry(archived_name).original)
+            .collect::<BTreeSet<String>>()
+            .into_iter()
+            .collect();
+        let original = if self.exact {
+            if !originals.contains(&name) {
+                return Err(AppError::NotFound(name).into());
+            }
+            name
+        } else {
+            match resolve::resolve(&name, &originals) {
+                Resolved::Found(original) => original,
+                Resolved::Ambiguous(suggestions) => {
+                    return Err(AppError::Ambiguous(name, suggestions).into())
+                }
+                Resolved::NotFound => return Err(AppError::NotFound(name).into()),
+            }
+        };
+        let versions = manifest.versions(&original, &names);
+        Ok((original, versions))
     }
 
-    fn handle_config_list(&self) -> Result<Message> {
-        Ok(Message::ConfigValues(
-            self.get_config_values(&config::value_names::ALL)?,
-        ))
+    fn trash_dir(&self) -> PathBuf {
+        self.rn_root_dir.join(TRASH_DIR_NAME)
     }
 
-    fn handle_archive(&mut self, args: cli::ArchiveArgs) -> Result<Message> {
-        match args.subcommand {
-            cli::ArchiveSubcommand::Save(args) => self.handle_archive_save(args),
-            cli::ArchiveSubcommand::List => self.handle_archive_list(),
-            cli::ArchiveSubcommand::Open(args) => self.handle_archive_open(args),
-            cli::ArchiveSubcommand::Restore(args) => self.handle_archive_restore(args),
-            cli::ArchiveSubcommand::Remove(args) => self.handle_archive_remove(args),
+    fn load_trash(&self) -> Result<Trash> {
+        let trash_file = self.trash_dir().join(TRASH_FILE_NAME);
+        if !self.fs.exists(&trash_file)? {
+            return Ok(Trash::default());
         }
+        // unlike the index the manifest can't be rebuilt, so it's not
+        // silently replaced
+        serde_json::from_str(&self.fs.read_file(&trash_file)?)
+            .map_err(|e| InternalError(e.into()).into())
     }
 
-    fn handle_archive_save(&mut self, args: cli::ArchiveSaveArgs) -> Result<Message> {
-        let name = args.name;
-        let active_path = self.get_note_path(name.as_str(), NoteType::Active);
-        if !self.fs.exists(&active_path)? {
-            return Err(AppError::NotFound(name).into());
+    fn save_trash(&mut self, trash: &Trash) -> Result<()> {
+        let json = serde_json::to_string_pretty(trash).map_err(|e| InternalError(e.into()))?;
+        let trash_file = self.trash_dir().join(TRASH_FILE_NAME);
+        self.record_change(&trash_file)?;
+        self.fs.write_file(&trash_file, &json)
+    }
+
+    /// Moves a note to the trash, or deletes it right away if `permanent`.
+    fn remove_note(&mut self, name: &str, note_type: NoteType, permanent: bool) -> Result<()> {
+        let path = self.get_note_path(name, note_type)?;
+        if permanent {
+            self.delete_note_file(&path, note_type)?;
+            if matches!(note_type, NoteType::Archived) {
+                let mut manifest = self.load_archive_manifest(&self.notebook)?;
+                if manifest.entries.remove(name).is_some() {
+                    self.save_archive_manifest(&self.notebook.clone(), &manifest)?;
+                }
+            }
+        } else {
+            let mut trash = self.load_trash()?;
+            let now = Local::now();
+            let id = trash.new_id(now);
+            let trash_dir = self.trash_dir();
+            if !self.fs.exists(&trash_dir)? {
+                self.fs.create_dir(&trash_dir)?;
+            }
+            let trash_path = trash_dir.join(&id);
+            self.record_change(&path)?;
+            self.record_change(&trash_path)?;
+            self.fs.rename(&path, &trash_path)?;
+            self.delete_empty_parent_dirs(&path, note_type)?;
+            trash.entries.push(TrashEntry {
+                id,
+                notebook: self.notebook.clone(),
+                name: name.to_owned(),
+                archived: matches!(note_type, NoteType::Archived),
+                deleted: now.timestamp(),
+            });
+            self.save_trash(&trash)?;
         }
-        let time_stamp = Local::now().format("%d-%m-%Y-%H:%M:%S").to_string();
-        let archived_name = format!("{name}_{time_stamp}");
-        let archived_path = self.get_note_path(&archived_name, NoteType::Archived);
-        if self.fs.exists(&archived_path)? {
-            return Err(AppError::ArchiveAlreadyExists(archived_name).into());
+        self.update_index(&[(name, note_type)])
+    }
+
+    /// Deletes the files of trashed notes, files which are already gone are
+    /// skipped. The metadata of trashed archived notes goes with them, unless
+    /// a note of the same name was archived since.
+    fn delete_trashed_files(&mut self, entries: &[TrashEntry]) -> Result<()> {
+        for entry in entries {
+            let path = self.trash_dir().join(&entry.id);
+            if self.fs.exists(&path)? {
+                self.record_change(&path)?;
+                self.fs.delete_file(&path)?;
+            }
+            if !entry.archived || !self.notebook_exists(&entry.notebook)? {
+                continue;
+            }
+            let archived_path = self
+                .get_notebook_dir_path(&entry.notebook, NoteType::Archived)
+                .join(&entry.name);
+            let mut manifest = self.load_archive_manifest(&entry.notebook)?;
+            if !self.fs.exists(&archived_path)? && manifest.entries.remove(&entry.name).is_some() {
+                self.save_archive_manifest(&entry.notebook, &manifest)?;
+            }
         }
-        self.fs.copy(&active_path, &archived_path)?;
-        self.fs.delete_file(&active_path)?;
-        Ok(Message::ArchivedNote((name, archived_name)))
+        Ok(())
     }
 
-    fn handle_archive_list(&self) -> Result<Message> {
-        let archived_notes = self.fs.get_files(&self.get_dir_path(NoteType::Archived))?;
-        Ok(Message::Archive(archived_notes))
+    fn journal_file(&self) -> PathBuf {
+        self.rn_root_dir.join(UNDO_DIR_NAME).join(UNDO_FILE_NAME)
     }
 
-    fn handle_archive_open(&mut self, args: cli::ArchiveOpenArgs) -> Result<Message> {
-        if let Some(editor) = args.editor {
-            self.config.editor = editor;
+    fn blobs_dir(&self) -> PathBuf {
+        self.rn_root_dir.join(UNDO_DIR_NAME).join(BLOBS_DIR_NAME)
+    }
+
+    fn load_journal(&self) -> Result<Journal> {
+        let journal_file = self.journal_file();
+        if !self.fs.exists(&journal_file)? {
+            return Ok(Journal::default());
         }
-        self.open_note(args.name, NoteType::Archived)
+        serde_json::from_str(&self.fs.read_file(&journal_file)?)
+            .map_err(|e| InternalError(e.into()).into())
     }
 
-    fn handle_archive_restore(&mut self, args: cli::ArchiveRestoreArgs) -> Result<Message> {
-        let new_name = args.new_name.unwrap_or_else(|| {
-            match args.archive_name.rsplit_once('_') {
-                Some((name, _time_stamp)) => name,
-                None => args.archive_name.as_str(),
+    /// Saves the journal and deletes the stored contents no operation needs
+    /// anymore.
+    fn save_journal(&mut self, journal: &Journal) -> Result<()> {
+        let json = serde_json::to_string(journal).map_err(|e| InternalError(e.into()))?;
+        self.create_parent_dirs(&self.journal_file())?;
+        self.fs.write_file(&self.journal_file(), &json)?;
+        let blobs_dir = self.blobs_dir();
+        if !self.fs.exists(&blobs_dir)? {
+            return Ok(());
+        }
+        let blobs = journal.blobs();
+        for hash in self.fs.get_files(&blobs_dir)? {
+            if !blobs.contains_key(hash.as_str()) {
+                self.fs.delete_file(&blobs_dir.join(hash))?;
             }
-            .to_owned()
-        });
-        let path = self.get_note_path(new_name.as_str(), NoteType::Active);
-        if self.fs.exists(&path)? {
-            return Err(AppError::RestoreAlreadyExists(new_name).into());
         }
-        let archived_path = self.get_note_path(args.archive_name.as_str(), NoteType::Archived);
-        self.fs.copy(&archived_path, &path)?;
-        Ok(Message::RestoredNote((args.archive_name, new_name)))
+        Ok(())
     }
 
-    fn handle_archive_remove(&mut self, args: cli::ArchiveRemoveArgs) -> Result<Message> {
-        let name = args.name;
-        let path = self.get_note_path(name.as_str(), NoteType::Archived);
+    /// Stores `content` in the journal directory, unless it's already there.
+    fn store_blob(&mut self, content: &[u8]) -> Result<Blob> {
+        let blob = Blob::of(content);
+        let path = self.blobs_dir().join(&blob.hash);
         if !self.fs.exists(&path)? {
-            return Err(AppError::NotFound(name).into());
+            self.create_parent_dirs(&path)?;
+            self.fs.write_bytes(&path, content)?;
         }
-        self.fs.delete_file(&path)?;
-        Ok(Message::DeletedNote)
+        Ok(blob)
     }
 
-    pub fn handle_command(&mut self, command: cli::Cli) -> Result<Message> {
-        self.check_dir_structure()?;
-        match command.subcommand {
-            cli::Subcommand::New(args) => self.handle_new(args),
-            cli::Subcommand::Open(args) => self.handle_open(args),
-            cli::Subcommand::Remove(args) => self.handle_remove(args),
-            cli::Subcommand::List => self.handle_list(),
-            cli::Subcommand::Completions(args) => self.handle_completions(args),
-            cli::Subcommand::Config(args) => self.handle_config(args),
-            cli::Subcommand::Archive(args) => self.handle_archive(args),
+    /// Reads a note, compressed archived notes are decompressed.
+    fn read_note_file(&self, path: &Path) -> Result<String> {
+        archive::decode(self.fs.read_bytes(path)?)
+    }
+
+    /// Content of the file at `path` as stored on disk, `None` if there is no
+    /// such file.
+    fn read_bytes_if_file(&self, path: &Path) -> Result<Option<Vec<u8>>> {
+        if !self.fs.exists(path)? {
+            return Ok(None);
+        }
+        self.fs.read_bytes(path).map(Some)
+    }
+
+    /// Remembers the content of `path` before the running command changes it,
+    /// so the command can be undone.
+    fn record_change(&mut self, path: &Path) -> Result<()> {
+        let is_recorded = self
+            .recording
+            .as_ref()
+            .is_none_or(|changes| changes.iter().any(|change| change.path == path));
+        if is_recorded {
+            return Ok(());
+        }
+        let before = match self.read_bytes_if_file(path)? {
+            Some(content) => Some(self.store_blob(&content)?),
+            None => None,
+        };
+        if let Some(changes) = &mut self.recording {
+            changes.push(FileChange {
+                path: path.to_path_buf(),
+                before,
+                after: None,
+            });
+        }
+        Ok(())
+    }
+
+    /// Name and type of the note of the current notebook at `path`.
+    fn note_of_path(&self, path: &Path) -> Option<(String, NoteType)> {
+        [NoteType::Active, NoteType::Archived]
+            .into_iter()
+            .find_map(|note_type| {
+                let name = path.strip_prefix(self.get_dir_path(note_type)).ok()?;
+                let name: Vec<&str> = name
+                    .components()
+                    .map(|component| component.as_os_str().to_str())
+                    .collect::<Option<_>>()?;
+                // hidden files like the archive metadata aren't notes
+                if name.iter().any(|component| component.starts_with('.')) {
+                    return None;
+                }
+                Some((name.join("/"), note_type))
+            })
+    }
+
+    /// Adds the recorded changes of a finished command to the journal.
+    fn finish_recording(&mut self, label: &str, changes: Vec<FileChange>) -> Result<()> {
+        let mut recorded = Vec::new();
+        for mut change in changes {
+            change.after = self
+                .read_bytes_if_file(&change.path)?
+                .as_deref()
+                .map(Blob::of);
+            if change.before != change.after {
+                recorded.push(change);
+            }
+        }
+        if recorded.is_empty() {
+            return Ok(());
+        }
+        let mut description = label.to_owned();
+        let mut notes = BTreeSet::new();
+        for change in &recorded {
+            if let Some((name, note_type)) = self.note_of_path(&change.path) {
+                let label = note_label(&name, note_type);
+                if notes.insert(label.clone()) {
+                    description.push(' ');
+                    description.push_str(&label);
+                }
+            }
+        }
+        let mut journal = self.load_journal()?;
+        journal.push(Operation {
+            description,
+            time: Local::now().timestamp(),
+            changes: recorded,
+        });
+        self.save_journal(&journal)
+    }
+
+    /// Puts the files of an operation back into the state before it (`undo`)
+    /// or after it. Nothing is changed if a file isn't in the state the
+    /// operation left it in.
+    fn replay_changes(&mut self, changes: &[FileChange], undo: bool) -> Result<()> {
+        let states = |change: &FileChange| {
+            if undo {
+                (change.after.clone(), change.before.clone())
+            } else {
+                (change.before.clone(), change.after.clone())
+            }
+        };
+        for change in changes {
+            let (expected, _) = states(change);
+            let content = self.read_bytes_if_file(&change.path)?;
+            if content.as_deref().map(Blob::of) != expected {
+                return Err(AppError::ChangedSinceOperation(change.path.clone()).into());
+            }
+        }
+        // the current contents are needed to replay the operation the other
+        // way round
+        for change in changes {
+            if let Some(content) = self.read_bytes_if_file(&change.path)? {
+                self.store_blob(&content)?;
+            }
+        }
+        let mut notes = Vec::new();
+        for change in changes {
+            let (_, target) = states(change);
+            let note = self.note_of_path(&change.path);
+            if let Some(blob) = target {
+                let content = self.fs.read_bytes(&self.blobs_dir().join(&blob.hash))?;
+                self.create_parent_dirs(&change.path)?;
+                self.fs.write_bytes(&change.path, &content)?;
+            } else if self.fs.exists(&change.path)? {
+                self.fs.delete_file(&change.path)?;
+                if let Some((_, note_type)) = note {
+                    self.delete_empty_parent_dirs(&change.path, note_type)?;
+                }
+            }
+            notes.extend(note);
+        }
+        let notes: Vec<(&str, NoteType)> = notes
+            .iter()
+            .map(|(name, note_type)| (name.as_str(), *note_type))
+            .collect();
+        self.update_index(&notes)
+    }
+
+    fn index_file(&self) -> PathBuf {
+        let mut index_file = self.notebook_root_dir(&self.notebook);
+        index_file.push(INDEX_FILE_NAME);
+        index_file
+    }
+
+    fn load_index(&self) -> Result<SearchIndex> {
+        let index_file = self.index_file();
+        if !self.fs.exists(&index_file)? {
+            return Ok(SearchIndex::default());
         }
+        Ok(SearchIndex::from_json(&self.fs.read_file(&index_file)?))
+    }
+
+    fn save_index(&mut self, index: &SearchIndex) -> Result<()> {
+        let json = index.to_json().map_err(|e| InternalError(e.into()))?;
+        self.fs.write_file(&self.index_file(), &json)
+    }
+
+    fn index_note(&self, index: &mut SearchIndex, name: &str, note_type: NoteType) -> Result<()> {
+        let label = note_label(name, note_type);
+        let path = self.get_note_path(name, note_type)?;
+        if !self.fs.exists(&path)? {
+            index.remove(&label);
+            return Ok(());
+        }
+        let metadata = self.fs.metadata(&path)?;
+        if index.is_stale(&label, &metadata) {
+            // notes which aren't valid UTF-8 are indexed without content
+            let content = self.read_note_file(&path).unwrap_or_default();
+            index.insert(&label, &content, &metadata);
+        }
+        Ok(())
+    }
+
+    /// Brings the index up to date with all notes of the notebook, including
+    /// notes changed outside of rn.
+    fn refresh_index(&self, index: &mut SearchIndex) -> Result<bool> {
+        let before = index.clone();
+        let mut labels = BTreeSet::new();
+        for note_type in [NoteType::Active, NoteType::Archived] {
+            for name in self.fs.get_files_recursive(&self.get_dir_path(note_type))? {
+                self.index_note(index, &name, note_type)?;
+                labels.insert(note_label(&name, note_type));
+            }
+        }
+        let removed: Vec<String> = index
+            .labels()
+            .filter(|label| !labels.contains(*label))
+            .cloned()
+            .collect();
+        for label in removed {
+            index.remove(&label);
+        }
+        Ok(*index != before)
+    }
+
+    /// Updates the index entries of notes changed by a command. Nothing is
+    /// done if the index wasn't built yet, that happens on the first search.
+    fn update_index(&mut self, notes: &[(&str, NoteType)]) -> Result<()> {
+        if !self.fs.exists(&self.index_file())? {
+            return Ok(());
+        }
+        let mut index = self.load_index()?;
+        for (name, note_type) in notes {
+            self.index_note(&mut index, name, *note_type)?;
+        }
+        self.save_index(&index)
+    }
+
+    fn get_config_values<T: AsRef<str>>(&self, value_names: &[T]) -> Result<Vec<ConfigValue>> {
+        let mut config_values = Vec::new();
+        let config_file_path = config::config_file()?;
+        let config_exists = self.fs.exists(&config_file_path)?;
+        if !config_exists {
+            return Ok(config_values);
+        }
+        let config = PartialConfig::from_config_file(&self.fs)?;
+        for value_name in value_names {
+            let value_name = value_name.as_ref();
+            let value = match value_name {
+                config::value_names::EDITOR => config.editor.clone(),
+                config::value_names::ROOT_DIR => {
+                    config.root_dir.as_ref().map(|p| p.display().to_string())
+                }
+                config::value_names::PAGER => config.pager.clone(),
+                config::value_names::DEFAULT_TEMPLATE => config.default_template.clone(),
+                config::value_names::JOURNAL_DATE_FORMAT => config.journal_date_format.clone(),
+                config::value_names::JOURNAL_TEMPLATE => config.journal_template.clone(),
+                config::value_names::SNAPSHOT_ON_OPEN => {
+                    config.snapshot_on_open.map(|value| value.to_string())
+                }
+                config::value_names::ARCHIVE_KEEP_LAST => {
+                    config.archive_keep_last.map(|value| value.to_string())
+                }
+                config::value_names::ARCHIVE_KEEP_DAILY => {
+                    config.archive_keep_daily.map(|value| value.to_string())
+                }
+                config::value_names::ARCHIVE_KEEP_WEEKLY => {
+                    config.archive_keep_weekly.map(|value| value.to_string())
+                }
+                config::value_names::ARCHIVE_KEEP_MONTHLY => {
+                    config.archive_keep_monthly.map(|value| value.to_string())
+                }
+                config::value_names::ARCHIVE_MAX_AGE => config.archive_max_age.clone(),
+                config::value_names::ARCHIVE_FORMAT => {
+                    config.archive_format.map(|format| format.to_string())
+                }
+                _ => continue,
+            };
+            if let Some(value) = value {
+                config_values.push(ConfigValue {
+                    name: value_name.to_owned(),
+                    value,
+                });
+            }
+        }
+        Ok(config_values)
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    fn handle_new(&mut self, args: cli::NewArgs) -> Result<Message> {
+        let name = args.name;
+        let path = self.get_note_path(&name, NoteType::Active)?;
+        if self.fs.exists(&path)? {
+            return Err(AppError::AlreadyExists(name).into());
+        }
+        let template_name = if args.no_template {
+            None
+        } else {
+            args.template
+                .or_else(|| self.config.default_template.clone())
+        };
+        let mut content = match template_name {
+            Some(template_name) => self.render_template(&template_name, &name, args.vars)?,
+            None => String::new(),
+        };
+        let text = match args.message {
+            Some(message) => message,
+            None if args.stdin => self.terminal.read_stdin()?,
+            None => String::new(),
+        };
+        content.push_str(&format_entry(&text, args.stamp));
+        let content = front_matter::touch(&content, &front_matter_time(), true).unwrap_or(content);
+        self.create_parent_dirs(&path)?;
+        self.record_change(&path)?;
+        self.fs.create_file(&path)?;
+        if !content.is_empty() {
+            self.fs.append_file(&path, &content)?;
+        }
+        self.update_index(&[(&name, NoteType::Active)])?;
+        Ok(Message::CreatedNote)
+    }
+
+    /// Appending doesn't resolve the name, a missing note is created instead.
+    fn handle_append(&mut self, args: cli::AppendArgs) -> Result<Message> {
+        let name = args.name;
+        let path = self.get_note_path(&name, NoteType::Active)?;
+        let text = match args.message {
+            Some(message) => message,
+            None => self.terminal.read_stdin()?,
+        };
+        self.create_parent_dirs(&path)?;
+        self.record_change(&path)?;
+        self.fs
+            .append_file(&path, &format_entry(&text, args.stamp))?;
+        self.update_index(&[(&name, NoteType::Active)])?;
+        Ok(Message::AppendedNote { name })
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    fn handle_show(&mut self, args: cli::ShowArgs) -> Result<Message> {
+        self.show_notes(&args, NoteType::Active)
+    }
+
+    fn handle_move(&mut self, args: cli::MoveArgs) -> Result<Message> {
+        self.move_note(args, NoteType::Active)
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    fn handle_copy(&mut self, args: cli::CopyArgs) -> Result<Message> {
+        let source_type = if args.archive {
+            NoteType::Archived
+        } else {
+            NoteType::Active
+        };
+        let source = self.resolve_note(args.source, source_type)?;
+        let destination = target_name(&source, &args.destination);
+        let source_path = self.get_note_path(&source, source_type)?;
+        let destination_path = self.get_note_path(&destination, NoteType::Active)?;
+        if self.fs.exists(&destination_path)? {
+            return Err(AppError::AlreadyExists(destination).into());
+        }
+        self.create_parent_dirs(&destination_path)?;
+        self.record_change(&destination_path)?;
+        if args.only_header {
+            let content = self.read_note_file(&source_path)?;
+            let header = front_matter::split(&content).map_or("", |(header, _)| header);
+            self.fs.create_file(&destination_path)?;
+            self.fs.append_file(&destination_path, header)?;
+        } else if matches!(source_type, NoteType::Archived) {
+            let content = self.read_note_file(&source_path)?;
+            self.fs.write_file(&destination_path, &content)?;
+        } else {
+            self.fs.copy(&source_path, &destination_path)?;
+        }
+        self.update_index(&[(&destination, NoteType::Active)])?;
+        Ok(Message::CopiedNote {
+            from: note_label(&source, source_type),
+            to: destination,
+        })
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    fn handle_remove(&mut self, args: cli::RemoveArgs) -> Result<Message> {
+        let names = self.resolve_or_pick_notes(args.name, NoteType::Active, true)?;
+        for name in &names {
+            self.remove_note(name, NoteType::Active, args.permanent)?;
+        }
+        Ok(match names.len() {
+            0 => Message::Empty,
+            1 if args.permanent => Message::DeletedNote,
+            _ if args.permanent => Message::DeletedNotes { names },
+            _ => Message::TrashedNotes { names },
+        })
+    }
+
+    fn handle_today(&mut self, args: cli::JournalArgs) -> Result<Message> {
+        self.open_journal_note(Local::now().date_naive(), args)
+    }
+
+    fn handle_yesterday(&mut self, args: cli::JournalArgs) -> Result<Message> {
+        let today = Local::now().date_naive();
+        let yesterday = today.pred_opt().unwrap_or(today);
+        self.open_journal_note(yesterday, args)
+    }
+
+    fn handle_day(&mut self, args: cli::DayArgs) -> Result<Message> {
+        let format = self.config.journal_date_format.clone();
+        if let Some(date) = args.date {
+            let date = parse_date(&date, &format)?;
+            return self.open_journal_note(date, args.journal);
+        }
+        let from = args
+            .from
+            .map(|from| parse_date(&from, &format))
+            .transpose()?;
+        let to = args.to.map(|to| parse_date(&to, &format)).transpose()?;
+        let journal_dir = self.get_dir_path(NoteType::Active).join(JOURNAL_DIR_NAME);
+        if !self.fs.exists(&journal_dir)? {
+            return Ok(Message::Notebook { notes: Vec::new() });
+        }
+        let mut entries: Vec<(NaiveDate, String)> = self
+            .fs
+            .get_files_recursive(&journal_dir)?
+            .into_iter()
+            .filter_map(|name| {
+                let date = NaiveDate::parse_from_str(&name, &format).ok()?;
+                Some((date, format!("{JOURNAL_DIR_NAME}/{name}")))
+            })
+            .filter(|(date, _)| from.is_none_or(|from| *date >= from))
+            .filter(|(date, _)| to.is_none_or(|to| *date <= to))
+            .collect();
+        entries.sort();
+        let names = entries.into_iter().map(|(_, name)| name).collect();
+        Ok(Message::Notebook {
+            notes: self.note_infos(names, NoteType::Active)?,
+        })
+    }
+
+    /// Notes are filtered by name, tags and modification date, then sorted
+    /// and limited. Sorted, limited and long listings are flat.
+    fn handle_list(&self, args: &cli::ListArgs) -> Result<Message> {
+        let mut names = self
+            .fs
+            .get_files_recursive(&self.get_dir_path(NoteType::Active))?;
+        if let Some(pattern) = &args.pattern {
+            if args.regex {
+                let regex = regex::Regex::new(pattern)
+                    .map_err(|e| AppError::InvalidSearchPattern(e.to_string()))?;
+                names.retain(|name| regex.is_match(name));
+            } else {
+                names.retain(|name| resolve::glob_matches(pattern, name));
+            }
+        }
+        let filter = TagFilter::parse(&args.tags).map_err(AppError::InvalidTag)?;
+        if !filter.is_empty() {
+            let note_tags = self.tags_of_notes(&names)?;
+            names = names
+                .into_iter()
+                .zip(note_tags)
+                .filter(|(_, tags)| filter.matches(tags))
+                .map(|(name, _)| name)
+                .collect();
+        }
+        let since = args.since.as_deref().map(parse_iso_date).transpose()?;
+        let until = args.until.as_deref().map(parse_iso_date).transpose()?;
+        let mut notes = self.note_infos(names, NoteType::Active)?;
+        notes.retain(|note| {
+            let date = note.modified.date_naive();
+            since.is_none_or(|since| date >= since) && until.is_none_or(|until| date <= until)
+        });
+        notes.sort_by(|a, b| a.name.cmp(&b.name));
+        match args.sort.unwrap_or_default() {
+            cli::SortKey::Name => {}
+            cli::SortKey::Modified => notes.sort_by_key(|note| Reverse(note.modified)),
+            cli::SortKey::Created => notes.sort_by_key(|note| Reverse(note.created)),
+            cli::SortKey::Size => notes.sort_by_key(|note| Reverse(note.size)),
+        }
+        if args.reverse {
+            notes.reverse();
+        }
+        if let Some(limit) = args.limit {
+            notes.truncate(limit);
+        }
+        if args.long {
+            for note in &mut notes {
+                note.lines = Some(self.read_note_file(&note.path)?.lines().count());
+            }
+            return Ok(Message::LongList { notes });
+        }
+        if args.flat || args.sort.is_some() || args.reverse || args.limit.is_some() {
+            return Ok(Message::Notebook { notes });
+        }
+        Ok(Message::NoteTree { notes })
+    }
+
+    fn handle_open(&mut self, args: cli::OpenArgs) -> Result<Message> {
+        if let Some(editor) = args.editor {
+            self.config.editor = editor;
+        }
+        self.open_note(args.name, NoteType::Active)
+    }
+
+    #[allow(clippy::unused_self)]
+    #[allow(clippy::needless_pass_by_value)]
+    fn handle_completions(&self, args: cli::CompletionArgs) -> Result<Message> {
+        let script = match args.shell {
+            cli::Shell::Zsh => include_str!("../completions/_rn").to_owned(),
+        };
+        Ok(Message::CompletionScript { script })
+    }
+
+    fn handle_config(&mut self, args: cli::ConfigArgs) -> Result<Message> {
+        match args.subcommand {
+            cli::ConfigSubcommand::Generate(args) => self.handle_config_generate(args),
+            cli::ConfigSubcommand::Get(args) => self.handle_config_get(args),
+            cli::ConfigSubcommand::List => self.handle_config_list(),
+        }
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    fn handle_config_generate(&mut self, args: cli::ConfigGenerateArgs) -> Result<Message> {
+        let config_file_path = config::config_file()?;
+        let config_exists = self.fs.exists(&config_file_path)?;
+        if config_exists && !args.force {
+            return Err(AppError::ConfigAlreadyExists(config_file_path).into());
+        }
+        let config = Config::default();
+        let config_string = config.to_toml()?;
+        #[allow(clippy::unwrap_used)] // `config_file_path` is never empty
+        let dir_path = config_file_path.parent().unwrap();
+        self.fs.create_dir(dir_path)?;
+        self.record_change(&config_file_path)?;
+        self.fs.write_file(&config_file_path, &config_string)?;
+        Ok(Message::GeneratedConfig {
+            path: config_file_path,
+        })
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    fn handle_config_get(&self, args: cli::ConfigGetArgs) -> Result<Message> {
+        Ok(Message::ConfigValues {
+            values: self.get_config_values(&args.value_names)?,
+        })
+    }
+
+    fn handle_config_list(&self) -> Result<Message> {
+        Ok(Message::ConfigValues {
+            values: self.get_config_values(&config::value_names::ALL)?,
+        })
+    }
+
+    fn handle_archive(&mut self, args: cli::ArchiveArgs) -> Result<Message> {
+        match args.subcommand {
+            cli::ArchiveSubcommand::Save(args) => self.handle_archive_save(args),
+            cli::ArchiveSubcommand::List => self.handle_archive_list(),
+            cli::ArchiveSubcommand::Open(args) => self.handle_archive_open(args),
+            cli::ArchiveSubcommand::Show(args) => self.show_notes(&args, NoteType::Archived),
+            cli::ArchiveSubcommand::Restore(args) => self.handle_archive_restore(args),
+            cli::ArchiveSubcommand::Move(args) => self.move_note(args, NoteType::Archived),
+            cli::ArchiveSubcommand::Remove(args) => self.handle_archive_remove(args),
+            cli::ArchiveSubcommand::History(args) => self.handle_archive_history(args),
+            cli::ArchiveSubcommand::Diff(args) => self.handle_archive_diff(args),
+            cli::ArchiveSubcommand::Prune(args) => self.handle_archive_prune(args),
+            cli::ArchiveSubcommand::Compact => self.handle_archive_compact(),
+        }
+    }
+
+    fn handle_archive_save(&mut self, args: cli::ArchiveSaveArgs) -> Result<Message> {
+        let name = self.resolve_note(args.name, NoteType::Active)?;
+        let archived_name = self.archive_note(&name, false)?;
+        Ok(Message::ArchivedNote {
+            from: name,
+            to: archived_name,
+        })
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    fn handle_snapshot(&mut self, args: cli::SnapshotArgs) -> Result<Message> {
+        let name = self.resolve_note(args.name, NoteType::Active)?;
+        let archived_name = self.archive_note(&name, true)?;
+        Ok(Message::Snapshot {
+            from: name,
+            to: archived_name,
+        })
+    }
+
+    fn handle_meta(&mut self, args: cli::MetaArgs) -> Result<Message> {
+        match args.subcommand {
+            cli::MetaSubcommand::Get(args) => self.handle_meta_get(args),
+            cli::MetaSubcommand::Set(args) => self.handle_meta_set(args),
+            cli::MetaSubcommand::Unset(args) => self.handle_meta_unset(args),
+        }
+    }
+
+    fn handle_meta_get(&mut self, args: cli::MetaGetArgs) -> Result<Message> {
+        let name = self.resolve_note(args.name, NoteType::Active)?;
+        let content = self.read_note_file(&self.get_note_path(&name, NoteType::Active)?)?;
+        let value =
+            FrontMatter::parse(&content).and_then(|(front_matter, _)| front_matter.get(&args.key));
+        match value {
+            Some(value) => Ok(Message::MetaValue {
+                key: args.key,
+                value,
+            }),
+            None => Err(AppError::FieldNotFound(name, args.key).into()),
+        }
+    }
+
+    /// Notes without front matter get YAML front matter.
+    fn handle_meta_set(&mut self, args: cli::MetaSetArgs) -> Result<Message> {
+        if !front_matter::is_valid_key(&args.key) {
+            return Err(AppError::InvalidFieldName(args.key).into());
+        }
+        let name = self.resolve_note(args.name, NoteType::Active)?;
+        let content = self.read_note_file(&self.get_note_path(&name, NoteType::Active)?)?;
+        let (mut front_matter, body) =
+            FrontMatter::parse(&content).unwrap_or((FrontMatter::new(), &content));
+        front_matter.set(&args.key, &FieldValue::new(&args.key, args.values));
+        self.write_front_matter(&name, &front_matter, body)?;
+        Ok(Message::SetMeta {
+            name,
+            key: args.key,
+        })
+    }
+
+    fn handle_meta_unset(&mut self, args: cli::MetaUnsetArgs) -> Result<Message> {
+        let name = self.resolve_note(args.name, NoteType::Active)?;
+        let content = self.read_note_file(&self.get_note_path(&name, NoteType::Active)?)?;
+        let Some((mut front_matter, body)) = FrontMatter::parse(&content) else {
+            return Err(AppError::FieldNotFound(name, args.key).into());
+        };
+        if !front_matter.unset(&args.key) {
+            return Err(AppError::FieldNotFound(name, args.key).into());
+        }
+        self.write_front_matter(&name, &front_matter, body)?;
+        Ok(Message::UnsetMeta {
+            name,
+            key: args.key,
+        })
+    }
+
+    /// Replaces the front matter of the active note `name`, front matter
+    /// without any fields left is removed.
+    fn write_front_matter(
+        &mut self,
+        name: &str,
+        front_matter: &FrontMatter,
+        body: &str,
+    ) -> Result<()> {
+        let path = self.get_note_path(name, NoteType::Active)?;
+        let content = if front_matter.is_empty() {
+            body.to_owned()
+        } else {
+            format!("{front_matter}{body}")
+        };
+        self.record_change(&path)?;
+        self.fs.write_file(&path, &content)?;
+        self.update_index(&[(name, NoteType::Active)])
+    }
+
+    fn handle_tag(&mut self, args: cli::TagArgs) -> Result<Message> {
+        match args.subcommand {
+            cli::TagSubcommand::Add(args) => self.handle_tag_add(args),
+            cli::TagSubcommand::Remove(args) => self.handle_tag_remove(args),
+        }
+    }
+
+    /// Tags are added to the `tags` field, notes without front matter get YAML
+    /// front matter.
+    fn handle_tag_add(&mut self, args: cli::TagEditArgs) -> Result<Message> {
+        if let Some(tag) = args.tags.iter().find(|tag| !tags::is_valid_tag(tag)) {
+            return Err(AppError::InvalidTag(tag.clone()).into());
+        }
+        let name = self.resolve_note(args.name, NoteType::Active)?;
+        let content = self.read_note_file(&self.get_note_path(&name, NoteType::Active)?)?;
+        let (mut front_matter, body) =
+            FrontMatter::parse(&content).unwrap_or((FrontMatter::new(), &content));
+        let mut note_tags = tags::front_matter_tags(&front_matter);
+        for tag in &args.tags {
+            if !note_tags.contains(tag) {
+                note_tags.push(tag.clone());
+            }
+        }
+        front_matter.set(tags::TAGS, &FieldValue::List(note_tags));
+        self.write_front_matter(&name, &front_matter, body)?;
+        Ok(Message::TaggedNote {
+            name,
+            tags: args.tags,
+        })
+    }
+
+    /// Only tags of the front matter are removed, inline tags are part of the
+    /// text.
+    fn handle_tag_remove(&mut self, args: cli::TagEditArgs) -> Result<Message> {
+        let name = self.resolve_note(args.name, NoteType::Active)?;
+        let content = self.read_note_file(&self.get_note_path(&name, NoteType::Active)?)?;
+        let (mut front_matter, body) =
+            FrontMatter::parse(&content).unwrap_or((FrontMatter::new(), &content));
+        let mut note_tags = tags::front_matter_tags(&front_matter);
+        for tag in &args.tags {
+            let Some(index) = note_tags.iter().position(|note_tag| note_tag == tag) else {
+                return Err(AppError::TagNotFound(name, tag.clone()).into());
+            };
+            note_tags.remove(index);
+        }
+        if note_tags.is_empty() {
+            front_matter.unset(tags::TAGS);
+        } else {
+            front_matter.set(tags::TAGS, &FieldValue::List(note_tags));
+        }
+        self.write_front_matter(&name, &front_matter, body)?;
+        Ok(Message::UntaggedNote {
+            name,
+            tags: args.tags,
+        })
+    }
+
+    /// Tags of the active notes `names`, in the same order.
+    fn tags_of_notes(&self, names: &[String]) -> Result<Vec<BTreeSet<String>>> {
+        names
+            .iter()
+            .map(|name| {
+                let content = self.read_note_file(&self.get_note_path(name, NoteType::Active)?)?;
+                Ok(tags::note_tags(&content))
+            })
+            .collect()
+    }
+
+    fn handle_tags(&self) -> Result<Message> {
+        let names = self
+            .fs
+            .get_files_recursive(&self.get_dir_path(NoteType::Active))?;
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for tag in self.tags_of_notes(&names)?.into_iter().flatten() {
+            *counts.entry(tag).or_default() += 1;
+        }
+        let tags = counts
+            .into_iter()
+            .map(|(tag, count)| TagCount { tag, count })
+            .collect();
+        Ok(Message::Tags { tags })
+    }
+
+    fn handle_archive_list(&self) -> Result<Message> {
+        let archived_notes = self
+            .fs
+            .get_files_recursive(&self.get_dir_path(NoteType::Archived))?;
+        Ok(Message::Archive {
+            notes: self.note_infos(archived_notes, NoteType::Archived)?,
+        })
+    }
+
+    fn handle_archive_open(&mut self, args: cli::ArchiveOpenArgs) -> Result<Message> {
+        if let Some(editor) = args.editor {
+            self.config.editor = editor;
+        }
+        self.open_note(args.name, NoteType::Archived)
+    }
+
+    fn handle_archive_restore(&mut self, args: cli::ArchiveRestoreArgs) -> Result<Message> {
+        if args.new_name.is_some() && args.archive_names.len() > 1 {
+            return Err(AppError::NewNameForMultipleNotes.into());
+        }
+        let mut manifest = self.load_archive_manifest(&self.notebook)?;
+        // every note is checked before the first one is restored
+        let mut restores: Vec<(String, String)> = Vec::new();
+        for archive_name in args.archive_names {
+            let archive_name = self.resolve_note(archive_name, NoteType::Archived)?;
+            if restores.iter().any(|(name, _)| *name == archive_name) {
+                continue;
+            }
+            let archived_path = self.get_note_path(&archive_name, NoteType::Archived)?;
+            if !self.fs.exists(&archived_path)? {
+                return Err(AppError::NotFound(archive_name).into());
+            }
+            let new_name = args
+                .new_name
+                .clone()
+                .unwrap_or_else(|| manifest.entry(&archive_name).original);
+            let path = self.get_note_path(&new_name, NoteType::Active)?;
+            let is_restored = restores.iter().any(|(_, name)| *name == new_name);
+            if is_restored || self.fs.exists(&path)? {
+                return Err(AppError::RestoreAlreadyExists(new_name).into());
+            }
+            restores.push((archive_name, new_name));
+        }
+        for (archive_name, new_name) in &restores {
+            let archived_path = self.get_note_path(archive_name, NoteType::Archived)?;
+            let path = self.get_note_path(new_name, NoteType::Active)?;
+            self.create_parent_dirs(&path)?;
+            self.record_change(&path)?;
+            let content = self.read_note_file(&archived_path)?;
+            self.fs.write_file(&path, &content)?;
+            if !args.keep {
+                self.delete_note_file(&archived_path, NoteType::Archived)?;
+                manifest.entries.remove(archive_name);
+            }
+            self.update_index(&[
+                (new_name, NoteType::Active),
+                (archive_name, NoteType::Archived),
+            ])?;
+        }
+        if !args.keep {
+            self.save_archive_manifest(&self.notebook.clone(), &manifest)?;
+        }
+        let notes = restores
+            .into_iter()
+            .map(|(from, to)| RestoredNote { from, to })
+            .collect();
+        Ok(Message::RestoredNotes { notes })
+    }
+
+    fn handle_archive_remove(&mut self, args: cli::ArchiveRemoveArgs) -> Result<Message> {
+        let name = self.resolve_note(args.name, NoteType::Archived)?;
+        self.remove_note(&name, NoteType::Archived, args.permanent)?;
+        if args.permanent {
+            return Ok(Message::DeletedNote);
+        }
+        Ok(Message::TrashedNotes {
+            names: vec![note_label(&name, NoteType::Archived)],
+        })
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    fn handle_archive_prune(&mut self, args: cli::ArchivePruneArgs) -> Result<Message> {
+        let retention = self.retention()?;
+        if retention.is_empty() {
+            return Ok(Message::PrunedArchive {
+                names: Vec::new(),
+                dry_run: args.dry_run,
+            });
+        }
+        let names = self
+            .fs
+            .get_files_recursive(&self.get_dir_path(NoteType::Archived))?;
+        let mut manifest = self.load_archive_manifest(&self.notebook)?;
+        let originals: BTreeSet<String> = names
+            .iter()
+            .map(|name| manifest.entry(name).original)
+            .collect();
+        let mut pruned = Vec::new();
+        for original in originals {
+            // notes without a known archiving time are never pruned
+            let versions: Vec<(String, ArchiveEntry)> = manifest
+                .versions(&original, &names)
+                .into_iter()
+                .filter(|(_, entry)| entry.archived != 0)
+                .collect();
+            let times: Vec<i64> = versions.iter().map(|(_, entry)| entry.archived).collect();
+            for ((name, _), kept) in versions.into_iter().zip(retention.keep(&times)) {
+                if !kept {
+                    pruned.push(name);
+                }
+            }
+        }
+        if !args.dry_run && !pruned.is_empty() {
+            for name in &pruned {
+                let path = self.get_note_path(name, NoteType::Archived)?;
+                self.delete_note_file(&path, NoteType::Archived)?;
+                manifest.entries.remove(name);
+            }
+            self.save_archive_manifest(&self.notebook.clone(), &manifest)?;
+            let notes: Vec<(&str, NoteType)> = pruned
+                .iter()
+                .map(|name| (name.as_str(), NoteType::Archived))
+                .collect();
+            self.update_index(&notes)?;
+        }
+        Ok(Message::PrunedArchive {
+            names: pruned,
+            dry_run: args.dry_run,
+        })
+    }
+
+    fn handle_archive_compact(&mut self) -> Result<Message> {
+        let names = self
+            .fs
+            .get_files_recursive(&self.get_dir_path(NoteType::Archived))?;
+        let mut compacted = Vec::new();
+        let (mut size, mut compressed_size) = (0, 0);
+        for name in names {
+            let path = self.get_note_path(&name, NoteType::Archived)?;
+            let content = self.fs.read_bytes(&path)?;
+            if archive::is_compressed(&content) {
+                continue;
+            }
+            let compressed = archive::compress(&content)?;
+            self.record_change(&path)?;
+            self.fs.write_bytes(&path, &compressed)?;
+            size += content.len();
+            compressed_size += compressed.len();
+            compacted.push(name);
+        }
+        let notes: Vec<(&str, NoteType)> = compacted
+            .iter()
+            .map(|name| (name.as_str(), NoteType::Archived))
+            .collect();
+        self.update_index(&notes)?;
+        Ok(Message::CompactedArchive {
+            count: compacted.len(),
+            size,
+            compressed_size,
+        })
+    }
+
+    fn handle_archive_history(&self, args: cli::ArchiveHistoryArgs) -> Result<Message> {
+        let (_, versions) = self.archived_versions(args.name)?;
+        let versions = versions
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, entry))| ArchivedVersion {
+                version: i + 1,
+                archived: local_time(entry.archived),
+                name,
+            })
+            .collect();
+        Ok(Message::ArchiveHistory { versions })
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    fn handle_archive_diff(&self, args: cli::ArchiveDiffArgs) -> Result<Message> {
+        let (original, versions) = self.archived_versions(args.name)?;
+        if versions.len() == 1 && args.from.is_none() {
+            return Err(AppError::SingleVersion(original).into());
+        }
+        let to = args.to.unwrap_or(versions.len());
+        let from = args.from.unwrap_or(to.saturating_sub(1));
+        let version = |version: usize| {
+            version
+                .checked_sub(1)
+                .and_then(|i| versions.get(i))
+                .map(|(name, _)| name.as_str())
+                .ok_or_else(|| AppError::VersionNotFound(original.clone(), version))
+        };
+        let (from_name, to_name) = (version(from)?, version(to)?);
+        let read = |name: &str| self.read_note_file(&self.get_note_path(name, NoteType::Archived)?);
+        let (old, new) = (read(from_name)?, read(to_name)?);
+        let diff = similar::TextDiff::from_lines(&old, &new)
+            .unified_diff()
+            .header(from_name, to_name)
+            .to_string();
+        Ok(Message::Diff { diff })
+    }
+
+    fn handle_trash(&mut self, args: cli::TrashArgs) -> Result<Message> {
+        match args.subcommand {
+            cli::TrashSubcommand::List => self.handle_trash_list(),
+            cli::TrashSubcommand::Restore(args) => self.handle_trash_restore(args),
+            cli::TrashSubcommand::Empty => self.handle_trash_empty(),
+            cli::TrashSubcommand::Purge(args) => self.handle_trash_purge(args),
+        }
+    }
+
+    fn handle_trash_list(&self) -> Result<Message> {
+        let mut entries = self.load_trash()?.entries;
+        entries.sort_by_key(|entry| entry.deleted);
+        let entries = entries
+            .into_iter()
+            .map(|entry| {
+                let note_type = if entry.archived {
+                    NoteType::Archived
+                } else {
+                    NoteType::Active
+                };
+                let location = format!("{}:{}", entry.notebook, note_label(&entry.name, note_type));
+                TrashedNote {
+                    id: entry.id,
+                    deleted: local_time(entry.deleted),
+                    location,
+                }
+            })
+            .collect();
+        Ok(Message::Trash { entries })
+    }
+
+    fn handle_trash_restore(&mut self, args: cli::TrashRestoreArgs) -> Result<Message> {
+        let mut trash = self.load_trash()?;
+        let position = trash
+            .find(&args.entry)
+            .ok_or_else(|| AppError::NotInTrash(args.entry.clone()))?;
+        let entry = trash.entries.remove(position);
+        let note_type = if entry.archived {
+            NoteType::Archived
+        } else {
+            NoteType::Active
+        };
+        let name = args.new_name.unwrap_or_else(|| entry.name.clone());
+        check_note_name(&name)?;
+        if !self.notebook_exists(&entry.notebook)? {
+            return Err(AppError::NotebookNotFound(entry.notebook).into());
+        }
+        let path = self
+            .get_notebook_dir_path(&entry.notebook, note_type)
+            .join(&name);
+        if self.fs.exists(&path)? {
+            return Err(AppError::RestoreAlreadyExists(name).into());
+        }
+        self.create_parent_dirs(&path)?;
+        let trash_path = self.trash_dir().join(&entry.id);
+        self.record_change(&trash_path)?;
+        self.record_change(&path)?;
+        self.fs.rename(&trash_path, &path)?;
+        self.save_trash(&trash)?;
+        if entry.notebook == self.notebook {
+            self.update_index(&[(&name, note_type)])?;
+        }
+        let location = format!("{}:{}", entry.notebook, note_label(&name, note_type));
+        Ok(Message::RestoredNote {
+            from: entry.id,
+            to: location,
+        })
+    }
+
+    fn handle_trash_empty(&mut self) -> Result<Message> {
+        let mut trash = self.load_trash()?;
+        let entries = std::mem::take(&mut trash.entries);
+        self.delete_trashed_files(&entries)?;
+        self.save_trash(&trash)?;
+        Ok(Message::EmptiedTrash {
+            count: entries.len(),
+        })
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    fn handle_trash_purge(&mut self, args: cli::TrashPurgeArgs) -> Result<Message> {
+        let age = TimeDelta::from_std(args.older_than).unwrap_or(TimeDelta::MAX);
+        let Some(cutoff) = Local::now().checked_sub_signed(age) else {
+            return Ok(Message::EmptiedTrash { count: 0 });
+        };
+        let mut trash = self.load_trash()?;
+        let entries = trash.take_older_than(cutoff.timestamp());
+        self.delete_trashed_files(&entries)?;
+        self.save_trash(&trash)?;
+        Ok(Message::EmptiedTrash {
+            count: entries.len(),
+        })
+    }
+
+    fn handle_template(&mut self, args: cli::TemplateArgs) -> Result<Message> {
+        match args.subcommand {
+            cli::TemplateSubcommand::New(args) => self.handle_template_new(args),
+            cli::TemplateSubcommand::List => self.handle_template_list(),
+            cli::TemplateSubcommand::Edit(args) => self.handle_template_edit(args),
+            cli::TemplateSubcommand::Remove(args) => self.handle_template_remove(args),
+        }
+    }
+
+    fn handle_template_new(&mut self, args: cli::TemplateNewArgs) -> Result<Message> {
+        let name = args.name;
+        let path = self.get_template_path(&name)?;
+        if self.fs.exists(&path)? {
+            return Err(AppError::TemplateAlreadyExists(name).into());
+        }
+        self.create_parent_dirs(&path)?;
+        self.record_change(&path)?;
+        self.fs.create_file(&path)?;
+        let editor = args.editor.unwrap_or_else(|| self.config.editor.clone());
+        self.fs.open_file(&editor, &path)?;
+        Ok(Message::CreatedTemplate { name })
+    }
+
+    fn handle_template_list(&self) -> Result<Message> {
+        let templates_dir = self.templates_dir();
+        if !self.fs.exists(&templates_dir)? {
+            return Ok(Message::Templates { names: Vec::new() });
+        }
+        let mut templates = self.fs.get_files_recursive(&templates_dir)?;
+        templates.sort();
+        Ok(Message::Templates { names: templates })
+    }
+
+    fn handle_template_edit(&mut self, args: cli::TemplateEditArgs) -> Result<Message> {
+        let path = self.get_template_path(&args.name)?;
+        if !self.fs.exists(&path)? {
+            return Err(AppError::TemplateNotFound(args.name).into());
+        }
+        let editor = args.editor.unwrap_or_else(|| self.config.editor.clone());
+        self.fs.open_file(&editor, &path)?;
+        Ok(Message::Empty)
+    }
+
+    fn handle_template_remove(&mut self, args: cli::TemplateRemoveArgs) -> Result<Message> {
+        let name = args.name;
+        let path = self.get_template_path(&name)?;
+        if !self.fs.exists(&path)? {
+            return Err(AppError::TemplateNotFound(name).into());
+        }
+        self.record_change(&path)?;
+        self.fs.delete_file(&path)?;
+        Ok(Message::DeletedTemplate { name })
+    }
+
+    fn handle_notebook(&mut self, args: cli::NotebookArgs) -> Result<Message> {
+        match args.subcommand {
+            cli::NotebookSubcommand::Create(args) => self.handle_notebook_create(args),
+            cli::NotebookSubcommand::List => self.handle_notebook_list(),
+            cli::NotebookSubcommand::Remove(args) => self.handle_notebook_remove(args),
+            cli::NotebookSubcommand::Rename(args) => self.handle_notebook_rename(args),
+            cli::NotebookSubcommand::Use(args) => self.handle_notebook_use(args),
+        }
+    }
+
+    fn handle_notebook_create(&mut self, args: cli::NotebookCreateArgs) -> Result<Message> {
+        let name = args.name;
+        if self.notebook_exists(&name)? {
+            return Err(AppError::NotebookAlreadyExists(name).into());
+        }
+        self.create_notebook_dirs(&name)?;
+        Ok(Message::CreatedNotebook { name })
+    }
+
+    fn handle_notebook_list(&self) -> Result<Message> {
+        let mut names = vec![DEFAULT_NOTEBOOK.to_owned()];
+        let notebooks_dir = self.notebooks_dir();
+        if self.fs.exists(&notebooks_dir)? {
+            let mut named_notebooks = self.fs.get_files(&notebooks_dir)?;
+            named_notebooks.sort();
+            names.append(&mut named_notebooks);
+        }
+        let current = self.current_notebook()?;
+        let notebooks = names
+            .into_iter()
+            .map(|name| NotebookInfo {
+                current: name == current,
+                name,
+            })
+            .collect();
+        Ok(Message::Notebooks { notebooks })
+    }
+
+    fn handle_notebook_remove(&mut self, args: cli::NotebookRemoveArgs) -> Result<Message> {
+        let name = args.name;
+        if name == DEFAULT_NOTEBOOK {
+            return Err(AppError::DefaultNotebook.into());
+        }
+        if !self.notebook_exists(&name)? {
+            return Err(AppError::NotebookNotFound(name).into());
+        }
+        if !args.force {
+            for note_type in [NoteType::Active, NoteType::Archived] {
+                let dir = self.get_notebook_dir_path(&name, note_type);
+                if self.fs.exists(&dir)? && !self.fs.get_files(&dir)?.is_empty() {
+                    return Err(AppError::NotebookNotEmpty(name).into());
+                }
+            }
+        }
+        self.fs.delete_dir_all(&self.notebook_root_dir(&name))?;
+        if self.current_notebook()? == name {
+            self.fs.delete_file(&self.current_notebook_file())?;
+        }
+        Ok(Message::DeletedNotebook { name })
+    }
+
+    fn handle_notebook_rename(&mut self, args: cli::NotebookRenameArgs) -> Result<Message> {
+        let (name, new_name) = (args.name, args.new_name);
+        if name == DEFAULT_NOTEBOOK || new_name == DEFAULT_NOTEBOOK {
+            return Err(AppError::DefaultNotebook.into());
+        }
+        if !self.notebook_exists(&name)? {
+            return Err(AppError::NotebookNotFound(name).into());
+        }
+        if self.notebook_exists(&new_name)? {
+            return Err(AppError::NotebookAlreadyExists(new_name).into());
+        }
+        // trashed notes are restored by the name of their notebook
+        let trash = self.load_trash()?;
+        if trash.entries.iter().any(|entry| entry.notebook == name) {
+            return Err(AppError::NotebookInTrash(name).into());
+        }
+        let root_dir = self.notebook_root_dir(&name);
+        let new_root_dir = self.notebook_root_dir(&new_name);
+        self.fs.rename(&root_dir, &new_root_dir)?;
+        let mut journal = self.load_journal()?;
+        if journal.move_paths(&root_dir, &new_root_dir) {
+            self.save_journal(&journal)?;
+        }
+        if self.current_notebook()? == name {
+            self.fs
+                .write_file(&self.current_notebook_file(), &new_name)?;
+        }
+        Ok(Message::RenamedNotebook {
+            from: name,
+            to: new_name,
+        })
+    }
+
+    fn handle_notebook_use(&mut self, args: cli::NotebookUseArgs) -> Result<Message> {
+        let name = args.name;
+        if !self.notebook_exists(&name)? {
+            return Err(AppError::NotebookNotFound(name).into());
+        }
+        self.fs.write_file(&self.current_notebook_file(), &name)?;
+        Ok(Message::UsingNotebook { name })
+    }
+
+    /// Only notes in `candidates` are read, `None` means all notes have to be
+    /// scanned.
+    fn search_notes(
+        &self,
+        matcher: &Matcher,
+        note_type: NoteType,
+        candidates: Option<&BTreeSet<String>>,
+    ) -> Result<Vec<SearchMatch>> {
+        let dir = self.get_dir_path(note_type);
+        let mut names = self.fs.get_files_recursive(&dir)?;
+        names.sort();
+        let mut results = Vec::new();
+        for name in names {
+            let label = note_label(&name, note_type);
+            if candidates.is_some_and(|candidates| !candidates.contains(&label)) {
+                continue;
+            }
+            // notes which aren't valid UTF-8 can't contain the query
+            let Some(content) = self.read_note_file(&dir.join(&name)).ok() else {
+                continue;
+            };
+            results.append(&mut matcher.find_matches(&label, &content));
+        }
+        Ok(results)
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    fn handle_search(&mut self, args: cli::SearchArgs) -> Result<Message> {
+        let options = SearchOptions {
+            ignore_case: args.ignore_case,
+            whole_word: args.word,
+            regex: args.regex,
+        };
+        let matcher = Matcher::new(&args.query, options)?;
+        let mut index = self.load_index()?;
+        if self.refresh_index(&mut index)? || !self.fs.exists(&self.index_file())? {
+            self.save_index(&index)?;
+        }
+        let query_tokens: Vec<String> = index::tokenize(&args.query)
+            .map(|(_, token)| token)
+            .collect();
+        // regular expressions can match text which doesn't consist of tokens
+        let candidates = (!args.regex && !query_tokens.is_empty())
+            .then(|| index.candidates(&query_tokens, args.word));
+        let mut results = self.search_notes(&matcher, NoteType::Active, candidates.as_ref())?;
+        if args.archive {
+            results.append(&mut self.search_notes(
+                &matcher,
+                NoteType::Archived,
+                candidates.as_ref(),
+            )?);
+        }
+        if results.is_empty() {
+            return Err(AppError::NoMatches(args.query).into());
+        }
+        if args.ranked {
+            let scores = index.scores(&query_tokens);
+            let score = |label: &String| scores.get(label).copied().unwrap_or_default();
+            results.sort_by(|a, b| score(&b.note).total_cmp(&score(&a.note)));
+        }
+        let highlight = self.terminal.use_color();
+        Ok(Message::SearchResults {
+            matches: results,
+            highlight,
+        })
+    }
+
+    fn handle_undo(&mut self) -> Result<Message> {
+        let mut journal = self.load_journal()?;
+        let operation = journal.done.pop().ok_or(AppError::NothingToUndo)?;
+        self.replay_changes(&operation.changes, true)?;
+        let description = operation.description.clone();
+        journal.undone.push(operation);
+        self.save_journal(&journal)?;
+        Ok(Message::Undone { description })
+    }
+
+    fn handle_redo(&mut self) -> Result<Message> {
+        let mut journal = self.load_journal()?;
+        let operation = journal.undone.pop().ok_or(AppError::NothingToRedo)?;
+        self.replay_changes(&operation.changes, false)?;
+        let description = operation.description.clone();
+        journal.done.push(operation);
+        self.save_journal(&journal)?;
+        Ok(Message::Redone { description })
+    }
+
+    fn handle_history(&self) -> Result<Message> {
+        let journal = self.load_journal()?;
+        let done = journal.done.iter().map(|operation| (operation, false));
+        let undone = journal
+            .undone
+            .iter()
+            .rev()
+            .map(|operation| (operation, true));
+        let operations = done
+            .chain(undone)
+            .map(|(operation, undone)| HistoryEntry {
+                time: local_time(operation.time),
+                description: operation.description.clone(),
+                undone,
+            })
+            .collect();
+        Ok(Message::History { operations })
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    fn handle_index(&mut self, args: cli::IndexArgs) -> Result<Message> {
+        match args.subcommand {
+            cli::IndexSubcommand::Rebuild => self.handle_index_rebuild(),
+        }
+    }
+
+    fn handle_index_rebuild(&mut self) -> Result<Message> {
+        let mut index = SearchIndex::default();
+        self.refresh_index(&mut index)?;
+        self.save_index(&index)?;
+        Ok(Message::RebuiltIndex { count: index.len() })
+    }
+
+    pub fn handle_command(&mut self, command: cli::Cli) -> Result<Message> {
+        self.exact = command.exact;
+        self.format = command.format;
+        self.quiet = command.quiet;
+        self.check_dir_structure()?;
+        if !matches!(command.subcommand, cli::Subcommand::Notebook(_)) {
+            self.select_notebook(command.notebook)?;
+        }
+        let label = journal_label(&command.subcommand);
+        if label.is_some() {
+            self.recording = Some(Vec::new());
+        }
+        let result = match command.subcommand {
+            cli::Subcommand::New(args) => self.handle_new(args),
+            cli::Subcommand::Append(args) => self.handle_append(args),
+            cli::Subcommand::Open(args) => self.handle_open(args),
+            cli::Subcommand::Show(args) => self.handle_show(args),
+            cli::Subcommand::Move(args) => self.handle_move(args),
+            cli::Subcommand::Copy(args) => self.handle_copy(args),
+            cli::Subcommand::Remove(args) => self.handle_remove(args),
+            cli::Subcommand::Today(args) => self.handle_today(args),
+            cli::Subcommand::Yesterday(args) => self.handle_yesterday(args),
+            cli::Subcommand::Day(args) => self.handle_day(args),
+            cli::Subcommand::List(args) => self.handle_list(&args),
+            cli::Subcommand::Completions(args) => self.handle_completions(args),
+            cli::Subcommand::Config(args) => self.handle_config(args),
+            cli::Subcommand::Archive(args) => self.handle_archive(args),
+            cli::Subcommand::Snapshot(args) => self.handle_snapshot(args),
+            cli::Subcommand::Meta(args) => self.handle_meta(args),
+            cli::Subcommand::Tag(args) => self.handle_tag(args),
+            cli::Subcommand::Tags => self.handle_tags(),
+            cli::Subcommand::Notebook(args) => self.handle_notebook(args),
+            cli::Subcommand::Template(args) => self.handle_template(args),
+            cli::Subcommand::Trash(args) => self.handle_trash(args),
+            cli::Subcommand::Undo => self.handle_undo(),
+            cli::Subcommand::Redo => self.handle_redo(),
+            cli::Subcommand::History => self.handle_history(),
+            cli::Subcommand::Search(args) => self.handle_search(args),
+            cli::Subcommand::Index(args) => self.handle_index(args),
+        };
+        let changes = self.recording.take();
+        if let (Some(label), Some(changes), true) = (label, changes, result.is_ok()) {
+            self.finish_recording(label, changes)?;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)] // tests
+#[allow(clippy::unwrap_used)] // tests
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::mock_fs::MockFileSystem;
+    use crate::mock_terminal::MockTerminal;
+
+    fn test_app(notes: &[&str]) -> App<MockFileSystem, MockTerminal> {
+        let rn_root_dir = PathBuf::from("/rn");
+        let config = Config {
+            root_dir: Some(rn_root_dir.clone()),
+            ..Config::default()
+        };
+        App::new(
+            config,
+            MockFileSystem::new(rn_root_dir, notes),
+            MockTerminal::default(),
+        )
+        .unwrap()
+    }
+
+    fn run(app: &mut App<MockFileSystem, MockTerminal>, args: &[&str]) -> Result<Message> {
+        app.handle_command(cli::Cli::parse_from(args))
+    }
+
+    /// Names of the notes listed by `args`.
+    fn names(app: &mut App<MockFileSystem, MockTerminal>, args: &[&str]) -> Vec<String> {
+        let (Message::Notebook { notes } | Message::Archive { notes }) = run(app, args).unwrap()
+        else {
+            panic!()
+        };
+        notes.into_iter().map(|note| note.name).collect()
+    }
+
+    #[test]
+    fn test_new_nested() {
+        let mut app = test_app(&[]);
+        run(&mut app, &["rn", "new", "projects/api/design"]).unwrap();
+        assert!(app
+            .fs
+            .is_file(Path::new("/rn/notebook/projects/api/design")));
+        assert!(!app.fs.is_dir(Path::new("/rn/archive/projects")));
+    }
+
+    #[test]
+    fn test_archive_restore() {
+        let mut app = test_app(&[
+            "archive/a_01-01-2026-10:00:00",
+            "archive/b_01-01-2026-10:00:00",
+            "archive/c_01-01-2026-10:00:00",
+            "notebook/c",
+        ]);
+        // nothing is restored if one of the notes can't be
+        assert!(run(&mut app, &["rn", "archive", "restore", "a", "c"]).is_err());
+        assert!(!app.fs.is_file(Path::new("/rn/notebook/a")));
+        assert!(run(&mut app, &["rn", "archive", "restore", "a", "missing"]).is_err());
+        assert!(run(&mut app, &["rn", "archive", "restore", "a", "b", "-n", "x"]).is_err());
+
+        let message = run(&mut app, &["rn", "archive", "restore", "a", "b"]).unwrap();
+        let Message::RestoredNotes { notes } = message else {
+            panic!()
+        };
+        let restored: Vec<(String, String)> =
+            notes.into_iter().map(|note| (note.from, note.to)).collect();
+        assert_eq!(
+            restored,
+            [
+                ("a_01-01-2026-10:00:00".into(), "a".into()),
+                ("b_01-01-2026-10:00:00".into(), "b".into())
+            ]
+        );
+        assert!(app.fs.is_file(Path::new("/rn/notebook/a")));
+        assert!(!app
+            .fs
+            .is_file(Path::new("/rn/archive/a_01-01-2026-10:00:00")));
+
+        run(
+            &mut app,
+            &["rn", "archive", "restore", "c", "-n", "d", "--keep"],
+        )
+        .unwrap();
+        assert!(app.fs.is_file(Path::new("/rn/notebook/d")));
+        assert!(app
+            .fs
+            .is_file(Path::new("/rn/archive/c_01-01-2026-10:00:00")));
+    }
+
+    #[test]
+    fn test_invalid_note_names() {
+        let mut app = test_app(&[]);
+        for name in ["../escape", "a//b", "a/", ".hidden", "a/./b"] {
+            let err = run(&mut app, &["rn", "new", name]).unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<AppError>(),
+                Some(AppError::InvalidNoteName(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_list_tree_and_flat() {
+        let mut app = test_app(&[
+            "notebook/todo",
+            "notebook/projects/api/design",
+            "notebook/projects/web",
+        ]);
+        let tree = run(&mut app, &["rn", "ls"]).unwrap().to_string();
+        assert_eq!(tree, "projects/\n├── api/\n│   └── design\n└── web\ntodo\n");
+
+        let mut notes = names(&mut app, &["rn", "ls", "--flat"]);
+        notes.sort();
+        assert_eq!(notes, ["projects/api/design", "projects/web", "todo"]);
+    }
+
+    #[test]
+    fn test_list_sort_and_filter() {
+        let mut app = test_app(&["notebook/a", "notebook/b", "notebook/c"]);
+        app.fs
+            .write_file(Path::new("/rn/notebook/b"), "one\ntwo\n")
+            .unwrap();
+        let mut list = |args: &[&str]| names(&mut app, &[["rn", "ls"].as_slice(), args].concat());
+        assert_eq!(list(&["--flat"]), ["a", "b", "c"]);
+        assert_eq!(list(&["--sort", "modified"]), ["b", "c", "a"]);
+        assert_eq!(list(&["--sort", "created"]), ["c", "b", "a"]);
+        assert_eq!(list(&["--sort", "size"]), ["b", "a", "c"]);
+        assert_eq!(list(&["--sort", "size", "--reverse"]), ["c", "a", "b"]);
+        assert_eq!(list(&["--sort", "modified", "--limit", "1"]), ["b"]);
+        assert_eq!(list(&["b*", "--flat"]), ["b"]);
+        assert_eq!(list(&["[ac]", "--regex", "--flat"]), ["a", "c"]);
+        // the mock clock starts at the unix epoch
+        assert_eq!(list(&["--until", "1970-01-02", "--flat"]), ["a", "b", "c"]);
+        assert!(list(&["--since", "1970-01-03", "--flat"]).is_empty());
+        // dates don't depend on the journal date format
+        app.config.journal_date_format = String::from("%d.%m.%Y");
+        let notes = names(&mut app, &["rn", "ls", "--since", "1970-01-01", "--flat"]);
+        assert_eq!(notes, ["a", "b", "c"]);
+        let err = run(&mut app, &["rn", "ls", "--since", "03.01.1970"]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<AppError>(),
+            Some(AppError::InvalidDate(_, None))
+        ));
+        assert!(run(&mut app, &["rn", "ls", "(", "--regex"]).is_err());
+
+        let Message::LongList { notes } = run(&mut app, &["rn", "ls", "--long", "b"]).unwrap()
+        else {
+            panic!()
+        };
+        let [note] = notes.as_slice() else { panic!() };
+        assert_eq!((note.size, note.lines), (8, Some(2)));
+    }
+
+    #[test]
+    fn test_remove_cleans_empty_folders() {
+        let mut app = test_app(&["notebook/projects/api/design", "notebook/projects/web"]);
+        run(&mut app, &["rn", "rm", "projects/api/design"]).unwrap();
+        assert!(!app.fs.is_dir(Path::new("/rn/notebook/projects/api")));
+        assert!(app.fs.is_dir(Path::new("/rn/notebook/projects")));
+
+        run(&mut app, &["rn", "rm", "projects/web"]).unwrap();
+        assert!(!app.fs.is_dir(Path::new("/rn/notebook/projects")));
+        assert!(app.fs.is_dir(Path::new("/rn/notebook")));
+    }
+
+    #[test]
+    fn test_remove_keeps_folders_with_hidden_files() {
+        let mut app = test_app(&["notebook/projects/todo", "notebook/projects/.keep"]);
+        run(&mut app, &["rn", "rm", "projects/todo"]).unwrap();
+        assert!(app.fs.is_file(Path::new("/rn/notebook/projects/.keep")));
+    }
+
+    #[test]
+    fn test_archive_save_keeps_folders() {
+        let mut app = test_app(&["notebook/projects/api/design"]);
+        run(&mut app, &["rn", "archive", "save", "projects/api/design"]).unwrap();
+        assert!(!app.fs.is_dir(Path::new("/rn/notebook/projects")));
+
+        let archived = names(&mut app, &["rn", "archive", "ls"]);
+        let [archived_name] = archived.as_slice() else {
+            panic!()
+        };
+        assert!(archived_name.starts_with("projects/api/design_"));
+
+        run(&mut app, &["rn", "archive", "restore", archived_name]).unwrap();
+        assert!(app
+            .fs
+            .is_file(Path::new("/rn/notebook/projects/api/design")));
+    }
+
+    #[test]
+    fn test_search() {
+        let mut app = test_app(&[
+            "notebook/todo",
+            "notebook/projects/api",
+            "archive/old_01-01-2026-10:00:00",
+        ]);
+        app.fs
+            .write_file(Path::new("/rn/notebook/todo"), "buy milk\nfix API docs\n")
+            .unwrap();
+        app.fs
+            .write_file(Path::new("/rn/notebook/projects/api"), "REST api\n")
+            .unwrap();
+        app.fs
+            .write_file(Path::new("/rn/archive/old_01-01-2026-10:00:00"), "api v1\n")
+            .unwrap();
+
+        let output = run(&mut app, &["rn", "search", "-i", "api"])
+            .unwrap()
+            .to_string();
+        assert_eq!(output, "projects/api:1: REST api\ntodo:2: fix API docs\n");
+
+        let output = run(&mut app, &["rn", "search", "--archive", "api"])
+            .unwrap()
+            .to_string();
+        assert_eq!(
+            output,
+            "projects/api:1: REST api\narchive:old_01-01-2026-10:00:00:1: api v1\n"
+        );
+
+        let message = run(&mut app, &["rn", "search", "milk"]).unwrap();
+        assert!(matches!(
+            message,
+            Message::SearchResults {
+                highlight: false,
+                ..
+            }
+        ));
+        app.terminal.set_terminal(true);
+        let message = run(&mut app, &["rn", "search", "milk"]).unwrap();
+        assert!(matches!(
+            message,
+            Message::SearchResults {
+                highlight: true,
+                ..
+            }
+        ));
+        assert!(message.to_string().contains("\x1b["));
+
+        let err = run(&mut app, &["rn", "search", "bread"]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<AppError>(),
+            Some(AppError::NoMatches(_))
+        ));
+    }
+
+    #[test]
+    fn test_search_index() {
+        let mut app = test_app(&["notebook/a", "notebook/b"]);
+        app.fs
+            .write_file(Path::new("/rn/notebook/a"), "rust rust\n")
+            .unwrap();
+        app.fs
+            .write_file(Path::new("/rn/notebook/b"), "cooking and rust\n")
+            .unwrap();
+        let index_file = Path::new("/rn/.index.json");
+
+        // the index is built on first use
+        assert!(!app.fs.is_file(index_file));
+        let output = run(&mut app, &["rn", "search", "--ranked", "rust"])
+            .unwrap()
+            .to_string();
+        assert_eq!(output, "a:1: rust rust\nb:1: cooking and rust\n");
+        assert!(app.fs.is_file(index_file));
+
+        // changes of rn commands are applied incrementally
+        run(&mut app, &["rn", "new", "c"]).unwrap();
+        run(&mut app, &["rn", "rm", "b"]).unwrap();
+        let index = app.load_index().unwrap();
+        assert_eq!(index.labels().collect::<Vec<_>>(), ["a", "c"]);
+
+        // changes made outside of rn are detected
+        app.fs
+            .write_file(Path::new("/rn/notebook/c"), "cooking\n")
+            .unwrap();
+        let output = run(&mut app, &["rn", "search", "cook"])
+            .unwrap()
+            .to_string();
+        assert_eq!(output, "c:1: cooking\n");
+
+        let output = run(&mut app, &["rn", "index", "rebuild"])
+            .unwrap()
+            .to_string();
+        assert_eq!(output, "Indexed 2 notes\n");
+    }
+
+    #[test]
+    fn test_resolve_note_names() {
+        let mut app = test_app(&[
+            "notebook/projects/api/design",
+            "notebook/projects/api/notes",
+            "notebook/todo",
+        ]);
+        run(&mut app, &["rn", "open", "desgn"]).unwrap();
+        run(&mut app, &["rn", "open", "tod"]).unwrap();
+        assert_eq!(
+            app.fs.opened_files(),
+            &[
+                PathBuf::from("/rn/notebook/projects/api/design"),
+                PathBuf::from("/rn/notebook/todo")
+            ]
+        );
+
+        let err = run(&mut app, &["rn", "rm", "projects/api"]).unwrap_err();
+        let Some(AppError::Ambiguous(_, suggestions)) = err.downcast_ref::<AppError>() else {
+            panic!()
+        };
+        assert_eq!(suggestions, &["projects/api/notes", "projects/api/design"]);
+
+        let err = run(&mut app, &["rn", "--exact", "rm", "tod"]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<AppError>(),
+            Some(AppError::NotFound(_))
+        ));
+
+        run(&mut app, &["rn", "archive", "save", "todo"]).unwrap();
+        run(&mut app, &["rn", "archive", "rm", "todo"]).unwrap();
+        let archived = names(&mut app, &["rn", "archive", "ls"]);
+        assert!(archived.is_empty());
+    }
+
+    #[test]
+    fn test_pick_notes() {
+        let mut app = test_app(&["notebook/a", "notebook/b", "notebook/c"]);
+        app.terminal.set_picked(&["b"]);
+        run(&mut app, &["rn", "open"]).unwrap();
+        assert_eq!(app.fs.opened_files(), &[PathBuf::from("/rn/notebook/b")]);
+
+        // cancelled picker
+        let message = run(&mut app, &["rn", "rm"]).unwrap();
+        assert!(matches!(message, Message::Empty));
+
+        app.terminal.set_picked(&["a", "c"]);
+        run(&mut app, &["rn", "rm"]).unwrap();
+        let notes = names(&mut app, &["rn", "ls", "--flat"]);
+        assert_eq!(notes, ["b"]);
+    }
+
+    #[test]
+    fn test_show() {
+        let mut app = test_app(&["notebook/a", "notebook/b"]);
+        let a = PathBuf::from("/rn/notebook/a");
+        app.fs.write_file(&a, "1\n2\n3\n4").unwrap();
+        app.fs
+            .write_file(&PathBuf::from("/rn/notebook/b"), "b\n")
+            .unwrap();
+
+        let show = |app: &mut App<MockFileSystem, MockTerminal>, args: &[&str]| {
+            let Message::NoteContents { notes } = run(app, args).unwrap() else {
+                panic!()
+            };
+            notes
+                .into_iter()
+                .map(|note| (note.name, note.content))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            show(&mut app, &["rn", "show", "a"]),
+            [("a".into(), "1\n2\n3\n4".into())]
+        );
+        let [(_, content)] = &show(&mut app, &["rn", "cat", "a", "--lines", "2:3"])[..] else {
+            panic!()
+        };
+        assert_eq!(content, "2\n3\n");
+        let [(_, content)] = &show(&mut app, &["rn", "cat", "a", "--lines", "3:"])[..] else {
+            panic!()
+        };
+        assert_eq!(content, "3\n4");
+        let [(_, content)] = &show(&mut app, &["rn", "cat", "a", "--head", "1"])[..] else {
+            panic!()
+        };
+        assert_eq!(content, "1\n");
+        let [(_, content)] = &show(&mut app, &["rn", "cat", "a", "--tail", "9"])[..] else {
+            panic!()
+        };
+        assert_eq!(content, "1\n2\n3\n4");
+
+        let message = run(&mut app, &["rn", "show", "a", "b", "--head", "1"]).unwrap();
+        assert_eq!(message.to_string(), "==> a <==\n1\n\n==> b <==\nb\n");
+
+        app.terminal.set_terminal(true);
+        let message = run(&mut app, &["rn", "show", "b"]).unwrap();
+        assert!(matches!(message, Message::Empty));
+        assert_eq!(app.terminal.paged(), &["b\n"]);
+    }
+
+    #[test]
+    fn test_archive_show() {
+        let mut app = test_app(&["archive/old_01-01-2026-10:00:00"]);
+        let message = run(&mut app, &["rn", "archive", "show", "old"]).unwrap();
+        let Message::NoteContents { notes } = message else {
+            panic!()
+        };
+        let [NoteContent { name, content }] = &notes[..] else {
+            panic!()
+        };
+        assert_eq!(
+            (name.as_str(), content.as_str()),
+            ("old_01-01-2026-10:00:00", "")
+        );
+        assert!(run(&mut app, &["rn", "show", "old"]).is_err());
+    }
+
+    #[test]
+    fn test_new_with_content() {
+        let mut app = test_app(&[]);
+        run(&mut app, &["rn", "new", "a", "-m", "first line"]).unwrap();
+        let a = PathBuf::from("/rn/notebook/a");
+        assert_eq!(app.fs.read_file(&a).unwrap(), "first line\n");
+
+        app.terminal.set_stdin("piped\ntext\n");
+        run(&mut app, &["rn", "new", "logs/b", "--stdin"]).unwrap();
+        let b = PathBuf::from("/rn/notebook/logs/b");
+        assert_eq!(app.fs.read_file(&b).unwrap(), "piped\ntext\n");
+
+        run(&mut app, &["rn", "new", "c", "--stamp"]).unwrap();
+        let content = app.fs.read_file(&PathBuf::from("/rn/notebook/c")).unwrap();
+        assert!(content.starts_with('[') && content.ends_with("]\n"));
+    }
+
+    #[test]
+    fn test_append() {
+        let mut app = test_app(&[]);
+        run(&mut app, &["rn", "append", "logs/build", "-m", "one"]).unwrap();
+        app.terminal.set_stdin("two\nthree");
+        let message = run(&mut app, &["rn", "append", "logs/build"]).unwrap();
+        assert!(matches!(message, Message::AppendedNote { name } if name == "logs/build"));
+        let path = PathBuf::from("/rn/notebook/logs/build");
+        assert_eq!(app.fs.read_file(&path).unwrap(), "one\ntwo\nthree\n");
+
+        run(
+            &mut app,
+            &["rn", "append", "logs/build", "-m", "four", "--stamp"],
+        )
+        .unwrap();
+        let content = app.fs.read_file(&path).unwrap();
+        let Some((_, entry)) = content.split_once("three\n") else {
+            panic!()
+        };
+        assert!(entry.starts_with('[') && entry.ends_with("]\nfour\n"));
+
+        // no fuzzy resolution, "log" is created next to "logs/build"
+        run(&mut app, &["rn", "append", "log", "-m", "x"]).unwrap();
+        assert!(app.fs.is_file(&PathBuf::from("/rn/notebook/log")));
+        assert!(run(&mut app, &["rn", "append", "logs", "-m", "x"]).is_err());
+    }
+
+    #[test]
+    fn test_templates() {
+        let mut app = test_app(&["templates/meeting"]);
+        let template = PathBuf::from("/rn/templates/meeting");
+        app.fs
+            .write_file(
+                &template,
+                "# {{name}} ({{notebook}})\n{{date}} {{ room }}\n",
+            )
+            .unwrap();
+
+        assert!(run(&mut app, &["rn", "new", "a", "-t", "meeting"]).is_err());
+        assert!(!app.fs.is_file(&PathBuf::from("/rn/notebook/a")));
+        assert!(run(&mut app, &["rn", "new", "a", "-t", "missing"]).is_err());
+
+        run(
+            &mut app,
+            &[
+                "rn", "new", "a", "-t", "meeting", "--var", "room=A1", "-m", "text",
+            ],
+        )
+        .unwrap();
+        let content = app.fs.read_file(&PathBuf::from("/rn/notebook/a")).unwrap();
+        let date = Local::now().format(DATE_FORMAT).to_string();
+        assert_eq!(content, format!("# a (default)\n{date} A1\ntext\n"));
+
+        app.config.default_template = Some("meeting".into());
+        run(&mut app, &["rn", "new", "b", "--var", "room=B2"]).unwrap();
+        let content = app.fs.read_file(&PathBuf::from("/rn/notebook/b")).unwrap();
+        assert!(content.starts_with("# b"));
+        run(&mut app, &["rn", "new", "c", "--no-template"]).unwrap();
+        let content = app.fs.read_file(&PathBuf::from("/rn/notebook/c")).unwrap();
+        assert!(content.is_empty());
+    }
+
+    #[test]
+    fn test_template_management() {
+        let mut app = test_app(&[]);
+        let Message::Templates { names: templates } =
+            run(&mut app, &["rn", "template", "ls"]).unwrap()
+        else {
+            panic!()
+        };
+        assert!(templates.is_empty());
+
+        run(&mut app, &["rn", "template", "new", "daily"]).unwrap();
+        let path = PathBuf::from("/rn/templates/daily");
+        assert!(app.fs.is_file(&path));
+        assert!(run(&mut app, &["rn", "template", "new", "daily"]).is_err());
+        run(&mut app, &["rn", "template", "edit", "daily"]).unwrap();
+        assert_eq!(app.fs.opened_files(), &[path.clone(), path.clone()]);
+
+        let Message::Templates { names: templates } =
+            run(&mut app, &["rn", "template", "ls"]).unwrap()
+        else {
+            panic!()
+        };
+        assert_eq!(templates, ["daily"]);
+
+        run(&mut app, &["rn", "template", "rm", "daily"]).unwrap();
+        assert!(!app.fs.is_file(&path));
+        assert!(run(&mut app, &["rn", "template", "edit", "daily"]).is_err());
+    }
+
+    #[test]
+    fn test_journal() {
+        let mut app = test_app(&["templates/daily"]);
+        app.fs
+            .write_file(&PathBuf::from("/rn/templates/daily"), "# {{date}}\n")
+            .unwrap();
+
+        let today = Local::now().date_naive();
+        run(&mut app, &["rn", "today"]).unwrap();
+        let path = PathBuf::from(format!("/rn/notebook/journal/{}", today.format("%Y-%m-%d")));
+        assert_eq!(app.fs.opened_files(), std::slice::from_ref(&path));
+
+        // existing notes are opened as they are
+        app.fs.write_file(&path, "entry\n").unwrap();
+        run(&mut app, &["rn", "today", "-t", "daily"]).unwrap();
+        assert_eq!(app.fs.read_file(&path).unwrap(), "entry\n");
+
+        app.config.journal_template = Some("daily".into());
+        app.config.journal_date_format = String::from("%d.%m.%Y");
+        run(&mut app, &["rn", "day", "2026-01-02"]).unwrap();
+        let path = PathBuf::from("/rn/notebook/journal/02.01.2026");
+        assert_eq!(app.fs.read_file(&path).unwrap(), "# 2026-01-02\n");
+        run(&mut app, &["rn", "day", "20.12.2025"]).unwrap();
+        assert!(run(&mut app, &["rn", "day", "yesterday"]).is_err());
+
+        // the note in the old date format isn't a journal note anymore
+        assert_eq!(
+            names(&mut app, &["rn", "day", "--list"]),
+            ["journal/20.12.2025", "journal/02.01.2026"]
+        );
+        assert_eq!(
+            names(&mut app, &["rn", "day", "--list", "--from", "2026-01-01"]),
+            ["journal/02.01.2026"]
+        );
+        assert_eq!(
+            names(&mut app, &["rn", "day", "--list", "--to", "31.12.2025"]),
+            ["journal/20.12.2025"]
+        );
+
+        app.config.journal_date_format = String::from("%Q");
+        assert!(run(&mut app, &["rn", "today"]).is_err());
+    }
+
+    #[test]
+    fn test_move() {
+        let mut app = test_app(&["notebook/todo", "notebook/projects/api", "notebook/done"]);
+        let message = run(&mut app, &["rn", "mv", "todo", "inbox/todo"]).unwrap();
+        assert!(
+            matches!(message, Message::MovedNote { from: old, to: new } if old == "todo" && new == "inbox/todo")
+        );
+        assert!(app.fs.is_file(&PathBuf::from("/rn/notebook/inbox/todo")));
+        assert!(!app.fs.is_file(&PathBuf::from("/rn/notebook/todo")));
+
+        // moving into a folder keeps the name and removes empty folders
+        run(&mut app, &["rn", "mv", "projects/api", "archived/"]).unwrap();
+        assert!(app.fs.is_file(&PathBuf::from("/rn/notebook/archived/api")));
+        assert!(!app.fs.is_dir(&PathBuf::from("/rn/notebook/projects")));
+
+        assert!(run(&mut app, &["rn", "mv", "done", "inbox/todo"]).is_err());
+        assert!(app.fs.is_file(&PathBuf::from("/rn/notebook/done")));
+        run(&mut app, &["rn", "mv", "done", "inbox/todo", "--force"]).unwrap();
+        assert!(!app.fs.is_file(&PathBuf::from("/rn/notebook/done")));
+        assert!(run(&mut app, &["rn", "mv", "inbox/todo", "../x"]).is_err());
+    }
+
+    #[test]
+    fn test_move_between_notebooks() {
+        let mut app = test_app(&["notebook/todo", "notebooks/work/notebook/.keep"]);
+        assert!(run(
+            &mut app,
+            &["rn", "mv", "todo", "todo", "--to-notebook", "missing"]
+        )
+        .is_err());
+        let message = run(
+            &mut app,
+            &["rn", "mv", "todo", "todo", "--to-notebook", "work"],
+        )
+        .unwrap();
+        assert!(matches!(message, Message::MovedNote { to: new, .. } if new == "work:todo"));
+        assert!(app
+            .fs
+            .is_file(&PathBuf::from("/rn/notebooks/work/notebook/todo")));
+    }
+
+    #[test]
+    fn test_move_to_invalid_notebook() {
+        let mut app = test_app(&["notebook/todo"]);
+        for notebook in ["../..", ".."] {
+            let err = run(
+                &mut app,
+                &["rn", "mv", "todo", "todo", "--to-notebook", notebook],
+            )
+            .unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<AppError>(),
+                Some(AppError::InvalidNotebookName(_))
+            ));
+        }
+        assert!(app.fs.is_file(&PathBuf::from("/rn/notebook/todo")));
+    }
+
+    #[test]
+    fn test_archive_move() {
+        let mut app = test_app(&["archive/old_01-01-2026-10:00:00"]);
+        run(&mut app, &["rn", "archive", "mv", "old", "2026/"]).unwrap();
+        let archived = names(&mut app, &["rn", "archive", "ls"]);
+        assert_eq!(archived, ["2026/old_01-01-2026-10:00:00"]);
+    }
+
+    #[test]
+    fn test_archive_move_between_notebooks() {
+        let mut app = test_app(&["notebook/todo", "notebooks/work/notebook/.keep"]);
+        let message = run(&mut app, &["rn", "archive", "save", "todo"]).unwrap();
+        let Message::ArchivedNote {
+            to: archived_name, ..
+        } = message
+        else {
+            panic!()
+        };
+        let args = [
+            "rn",
+            "archive",
+            "mv",
+            &archived_name,
+            "old",
+            "--to-notebook",
+            "work",
+        ];
+        run(&mut app, &args).unwrap();
+        assert!(!app
+            .load_archive_manifest(DEFAULT_NOTEBOOK)
+            .unwrap()
+            .entries
+            .contains_key(&archived_name));
+        let message = run(
+            &mut app,
+            &["rn", "--notebook", "work", "archive", "history", "todo"],
+        );
+        let Message::ArchiveHistory { versions } = message.unwrap() else {
+            panic!()
+        };
+        assert!(
+            matches!(versions.as_slice(), [ArchivedVersion { version: 1, name, .. }] if name == "old")
+        );
+    }
+
+    #[test]
+    fn test_copy() {
+        let mut app = test_app(&[
+            "notebook/retros/sprint-1",
+            "archive/plan_01-01-2026-10:00:00",
+        ]);
+        let retro = PathBuf::from("/rn/notebook/retros/sprint-1");
+        app.fs
+            .write_file(&retro, "---\ntitle: Retro\n---\n# Went well\n")
+            .unwrap();
+
+        let message = run(&mut app, &["rn", "cp", "sprint-1", "retros/sprint-2"]).unwrap();
+        assert!(
+            matches!(message, Message::CopiedNote { from: source, .. } if source == "retros/sprint-1")
+        );
+        let copy = PathBuf::from("/rn/notebook/retros/sprint-2");
+        assert_eq!(
+            app.fs.read_file(&copy).unwrap(),
+            app.fs.read_file(&retro).unwrap()
+        );
+        assert!(run(&mut app, &["rn", "cp", "sprint-1", "retros/sprint-2"]).is_err());
+
+        run(
+            &mut app,
+            &["rn", "cp", "sprint-1", "sprint-3", "--only-header"],
+        )
+        .unwrap();
+        let header = app
+            .fs
+            .read_file(&PathBuf::from("/rn/notebook/sprint-3"))
+            .unwrap();
+        assert_eq!(header, "---\ntitle: Retro\n---\n");
+
+        run(&mut app, &["rn", "cp", "--archive", "plan", "plans/"]).unwrap();
+        let copy = PathBuf::from("/rn/notebook/plans/plan_01-01-2026-10:00:00");
+        assert!(app.fs.is_file(&copy));
+        assert!(app
+            .fs
+            .is_file(&PathBuf::from("/rn/archive/plan_01-01-2026-10:00:00")));
+    }
+
+    #[test]
+    fn test_trash() {
+        let mut app = test_app(&["notebook/projects/todo", "archive/old_01-01-2026-10:00:00"]);
+        let todo = PathBuf::from("/rn/notebook/projects/todo");
+        app.fs.write_file(&todo, "content").unwrap();
+
+        let message = run(&mut app, &["rn", "rm", "todo"]).unwrap();
+        assert!(matches!(message, Message::TrashedNotes { names } if names == ["projects/todo"]));
+        assert!(!app.fs.is_file(&todo));
+        assert!(!app.fs.is_dir(&PathBuf::from("/rn/notebook/projects")));
+        run(&mut app, &["rn", "archive", "rm", "old"]).unwrap();
+
+        let Message::Trash { entries } = run(&mut app, &["rn", "trash", "ls"]).unwrap() else {
+            panic!()
+        };
+        let locations: Vec<&str> = entries
+            .iter()
+            .map(|entry| entry.location.as_str())
+            .collect();
+        assert_eq!(
+            locations,
+            [
+                "default:projects/todo",
+                "default:archive:old_01-01-2026-10:00:00"
+            ]
+        );
+
+        // restoring by the original name
+        run(&mut app, &["rn", "new", "projects/todo"]).unwrap();
+        assert!(run(&mut app, &["rn", "trash", "restore", "projects/todo"]).is_err());
+        run(
+            &mut app,
+            &["rn", "trash", "restore", "projects/todo", "-n", "todo"],
+        )
+        .unwrap();
+        assert_eq!(
+            app.fs
+                .read_file(&PathBuf::from("/rn/notebook/todo"))
+                .unwrap(),
+            "content"
+        );
+        assert!(run(&mut app, &["rn", "trash", "restore", "projects/todo"]).is_err());
+
+        // restoring by id
+        let [_, TrashedNote { id, .. }] = &entries[..] else {
+            panic!()
+        };
+        run(&mut app, &["rn", "trash", "restore", id]).unwrap();
+        assert!(app
+            .fs
+            .is_file(&PathBuf::from("/rn/archive/old_01-01-2026-10:00:00")));
+    }
+
+    #[test]
+    fn test_trash_empty_and_purge() {
+        let mut app = test_app(&["notebook/a", "notebook/b", "notebook/c"]);
+        run(&mut app, &["rn", "rm", "a"]).unwrap();
+        let message = run(&mut app, &["rn", "trash", "purge", "--older-than", "1d"]).unwrap();
+        assert!(matches!(message, Message::EmptiedTrash { count: 0 }));
+        let message = run(&mut app, &["rn", "trash", "purge", "--older-than", "0s"]).unwrap();
+        assert!(matches!(message, Message::EmptiedTrash { count: 1 }));
+
+        run(&mut app, &["rn", "rm", "b", "--permanent"]).unwrap();
+        let message = run(&mut app, &["rn", "archive", "save", "c"]).unwrap();
+        let Message::ArchivedNote {
+            to: archived_name, ..
+        } = message
+        else {
+            panic!()
+        };
+        run(&mut app, &["rn", "archive", "rm", &archived_name]).unwrap();
+        run(&mut app, &["rn", "trash", "empty"]).unwrap();
+        let manifest = app.load_archive_manifest(DEFAULT_NOTEBOOK).unwrap();
+        assert!(!manifest.entries.contains_key(&archived_name));
+        let Message::Trash { entries } = run(&mut app, &["rn", "trash", "ls"]).unwrap() else {
+            panic!()
+        };
+        assert!(entries.is_empty());
+        assert!(app
+            .fs
+            .get_files(&PathBuf::from("/rn/.trash"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut app = test_app(&["notebook/projects/todo"]);
+        let todo = PathBuf::from("/rn/notebook/projects/todo");
+        app.fs.write_file(&todo, "content").unwrap();
+
+        run(&mut app, &["rn", "rm", "todo"]).unwrap();
+        let message = run(&mut app, &["rn", "undo"]).unwrap();
+        assert!(
+            matches!(message, Message::Undone { description } if description == "rm projects/todo")
+        );
+        assert_eq!(app.fs.read_file(&todo).unwrap(), "content");
+        let Message::Trash { entries } = run(&mut app, &["rn", "trash", "ls"]).unwrap() else {
+            panic!()
+        };
+        assert!(entries.is_empty());
+
+        run(&mut app, &["rn", "redo"]).unwrap();
+        assert!(!app.fs.is_file(&todo));
+        assert!(!app.fs.is_dir(&PathBuf::from("/rn/notebook/projects")));
+        assert!(run(&mut app, &["rn", "redo"]).is_err());
+
+        run(&mut app, &["rn", "undo"]).unwrap();
+        run(&mut app, &["rn", "mv", "projects/todo", "done"]).unwrap();
+        let Message::History { operations } = run(&mut app, &["rn", "history"]).unwrap() else {
+            panic!()
+        };
+        let descriptions: Vec<&str> = operations
+            .iter()
+            .map(|operation| operation.description.as_str())
+            .collect();
+        assert_eq!(descriptions, ["mv projects/todo done"]);
+    }
+
+    #[test]
+    fn test_undo_refuses_changed_files() {
+        let mut app = test_app(&[]);
+        run(&mut app, &["rn", "append", "log", "-m", "one"]).unwrap();
+        let log = PathBuf::from("/rn/notebook/log");
+        app.fs.write_file(&log, "edited").unwrap();
+        assert!(run(&mut app, &["rn", "undo"]).is_err());
+        assert_eq!(app.fs.read_file(&log).unwrap(), "edited");
+
+        app.fs.write_file(&log, "one\n").unwrap();
+        run(&mut app, &["rn", "undo"]).unwrap();
+        assert!(!app.fs.is_file(&log));
+        assert!(run(&mut app, &["rn", "undo"]).is_err());
+    }
+
+    #[test]
+    fn test_undo_restores_stored_bytes() {
+        let mut app = test_app(&["notebook/binary", "archive/log_01-03-2026-10:00:00"]);
+        let binary = PathBuf::from("/rn/notebook/binary");
+        app.fs.write_bytes(&binary, &[0xff, 0xfe, b'\n']).unwrap();
+        run(&mut app, &["rn", "rm", "binary", "--permanent"]).unwrap();
+        run(&mut app, &["rn", "undo"]).unwrap();
+        assert_eq!(app.fs.read_bytes(&binary).unwrap(), [0xff, 0xfe, b'\n']);
+
+        let log = PathBuf::from("/rn/archive/log_01-03-2026-10:00:00");
+        let compressed = archive::compress(b"build passed\n").unwrap();
+        app.fs.write_bytes(&log, &compressed).unwrap();
+        run(&mut app, &["rn", "archive", "rm", "log", "--permanent"]).unwrap();
+        run(&mut app, &["rn", "undo"]).unwrap();
+        assert_eq!(app.fs.read_bytes(&log).unwrap(), compressed);
+        run(&mut app, &["rn", "redo"]).unwrap();
+        assert!(!app.fs.is_file(&log));
+
+        // the content of the binary note isn't needed anymore since its
+        // undone removal can't be redone
+        let blobs = app
+            .fs
+            .get_files(&PathBuf::from("/rn/.journal/blobs"))
+            .unwrap();
+        assert_eq!(blobs, [Blob::of(&compressed).hash]);
+    }
+
+    #[test]
+    fn test_invalid_notebook_names() {
+        let mut app = test_app(&["notebook/todo"]);
+        let is_invalid = |result: Result<Message>| {
+            matches!(
+                result.unwrap_err().downcast_ref::<AppError>(),
+                Some(AppError::InvalidNotebookName(_))
+            )
+        };
+        for name in ["..", "../x", ""] {
+            let remove = run(&mut app, &["rn", "notebook", "rm", name, "--force"]);
+            assert!(is_invalid(remove));
+            let rename = run(&mut app, &["rn", "notebook", "rename", name, "x"]);
+            assert!(is_invalid(rename));
+            assert!(is_invalid(run(&mut app, &["rn", "notebook", "use", name])));
+            assert!(is_invalid(run(
+                &mut app,
+                &["rn", "--notebook", name, "new", "x"]
+            )));
+        }
+        assert!(app.fs.is_file(Path::new("/rn/notebook/todo")));
+
+        // a saved current notebook is checked as well
+        app.fs
+            .write_file(Path::new("/rn/.current_notebook"), "../..")
+            .unwrap();
+        assert!(is_invalid(run(&mut app, &["rn", "new", "x"])));
+    }
+
+    #[test]
+    fn test_notebook_rename_with_trash_and_journal() {
+        let mut app = test_app(&[]);
+        run(&mut app, &["rn", "notebook", "create", "work"]).unwrap();
+        run(&mut app, &["rn", "--notebook", "work", "new", "todo"]).unwrap();
+        run(&mut app, &["rn", "--notebook", "work", "rm", "todo"]).unwrap();
+        let err = run(&mut app, &["rn", "notebook", "rename", "work", "job"]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<AppError>(),
+            Some(AppError::NotebookInTrash(name)) if name == "work"
+        ));
+
+        run(&mut app, &["rn", "trash", "restore", "todo"]).unwrap();
+        run(&mut app, &["rn", "notebook", "rename", "work", "job"]).unwrap();
+        let todo = Path::new("/rn/notebooks/job/notebook/todo");
+        assert!(app.fs.is_file(todo));
+        // undoing the restore trashes the note of the renamed notebook
+        run(&mut app, &["rn", "undo"]).unwrap();
+        assert!(!app.fs.is_file(todo));
+        run(&mut app, &["rn", "undo"]).unwrap();
+        assert!(app.fs.is_file(todo));
+        run(&mut app, &["rn", "undo"]).unwrap();
+        assert!(!app.fs.is_file(todo));
+        assert!(!app.fs.is_dir(Path::new("/rn/notebooks/work")));
+    }
+
+    #[test]
+    fn test_read_only_commands_are_not_journaled() {
+        let mut app = test_app(&["notebook/a"]);
+        run(&mut app, &["rn", "ls"]).unwrap();
+        run(&mut app, &["rn", "cat", "a"]).unwrap();
+        assert!(!app.fs.is_dir(&PathBuf::from("/rn/.journal")));
+        let Message::History { operations } = run(&mut app, &["rn", "history"]).unwrap() else {
+            panic!()
+        };
+        assert!(operations.is_empty());
+    }
+
+    #[test]
+    fn test_archive_history_and_diff() {
+        let mut app = test_app(&["notebook/my_todo"]);
+        let todo = PathBuf::from("/rn/notebook/my_todo");
+        let mut archived_names = Vec::new();
+        for content in ["a\nb\n", "a\nc\n"] {
+            app.fs.write_file(&todo, content).unwrap();
+            let message = run(&mut app, &["rn", "archive", "save", "my_todo"]).unwrap();
+            let Message::ArchivedNote {
+                to: archived_name, ..
+            } = message
+            else {
+                panic!()
+            };
+            // archived names only differ by the second they were archived at
+            let archived_path = PathBuf::from("/rn/archive").join(&archived_name);
+            let renamed = format!("my_todo_v{}", archived_names.len() + 1);
+            run(&mut app, &["rn", "archive", "mv", &archived_name, &renamed]).unwrap();
+            assert!(!app.fs.is_file(&archived_path));
+            archived_names.push(renamed);
+        }
+
+        let message = run(&mut app, &["rn", "archive", "history", "my_todo"]).unwrap();
+        let Message::ArchiveHistory { versions } = message else {
+            panic!()
+        };
+        let names: Vec<(usize, &str)> = versions
+            .iter()
+            .map(|version| (version.version, version.name.as_str()))
+            .collect();
+        assert_eq!(names, [(1, "my_todo_v1"), (2, "my_todo_v2")]);
+
+        let message = run(&mut app, &["rn", "archive", "diff", "my_todo"]).unwrap();
+        let Message::Diff { diff } = message else {
+            panic!()
+        };
+        assert_eq!(
+            diff,
+            "--- my_todo_v1\n+++ my_todo_v2\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n"
+        );
+        assert!(run(&mut app, &["rn", "archive", "diff", "my_todo", "1", "3"]).is_err());
+        assert!(run(&mut app, &["rn", "archive", "history", "missing"]).is_err());
+
+        // the original name is restored, even though the archived name differs
+        run(&mut app, &["rn", "archive", "restore", "my_todo_v2"]).unwrap();
+        assert_eq!(app.fs.read_file(&todo).unwrap(), "a\nc\n");
+        let message = run(&mut app, &["rn", "archive", "history", "my_todo"]).unwrap();
+        assert!(matches!(message, Message::ArchiveHistory { versions } if versions.len() == 1));
+    }
+
+    #[test]
+    fn test_archive_diff_single_version() {
+        let mut app = test_app(&["notebook/my_todo"]);
+        run(&mut app, &["rn", "archive", "save", "my_todo"]).unwrap();
+        let err = run(&mut app, &["rn", "archive", "diff", "my_todo"]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<AppError>(),
+            Some(AppError::SingleVersion(name)) if name == "my_todo"
+        ));
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut app = test_app(&["notebook/plan"]);
+        let plan = PathBuf::from("/rn/notebook/plan");
+        app.fs.write_file(&plan, "draft").unwrap();
+        let message = run(&mut app, &["rn", "snapshot", "plan"]).unwrap();
+        let Message::Snapshot {
+            from: name,
+            to: archived_name,
+        } = message
+        else {
+            panic!()
+        };
+        assert_eq!(name, "plan");
+        assert!(app.fs.is_file(&plan));
+        let archived_path = PathBuf::from("/rn/archive").join(&archived_name);
+        assert_eq!(app.fs.read_file(&archived_path).unwrap(), "draft");
+    }
+
+    #[test]
+    fn test_snapshot_on_open() {
+        let mut app = test_app(&["notebook/plan"]);
+        app.config.snapshot_on_open = true;
+        let versions = |app: &mut App<MockFileSystem, MockTerminal>| {
+            let message = run(app, &["rn", "archive", "history", "plan"]).ok();
+            match message {
+                Some(Message::ArchiveHistory { versions }) => versions.len(),
+                _ => 0,
+            }
+        };
+        run(&mut app, &["rn", "open", "plan"]).unwrap();
+        assert_eq!(versions(&mut app), 1);
+        // unchanged since the last snapshot
+        run(&mut app, &["rn", "open", "plan"]).unwrap();
+        assert_eq!(versions(&mut app), 1);
+
+        app.config.snapshot_on_open = false;
+        let plan = PathBuf::from("/rn/notebook/plan");
+        app.fs.write_file(&plan, "changed").unwrap();
+        run(&mut app, &["rn", "open", "plan"]).unwrap();
+        assert_eq!(versions(&mut app), 1);
+    }
+
+    #[test]
+    fn test_meta() {
+        let mut app = test_app(&["notebook/retro"]);
+        let retro = PathBuf::from("/rn/notebook/retro");
+        app.fs.write_file(&retro, "# Retro\n").unwrap();
+        assert!(run(&mut app, &["rn", "meta", "get", "retro", "title"]).is_err());
+        assert!(run(&mut app, &["rn", "meta", "set", "retro", "a b", "x"]).is_err());
+
+        run(
+            &mut app,
+            &["rn", "meta", "set", "retro", "title", "Retro Q3"],
+        )
+        .unwrap();
+        run(&mut app, &["rn", "meta", "set", "retro", "tags", "work"]).unwrap();
+        assert_eq!(
+            app.fs.read_file(&retro).unwrap(),
+            "---\ntitle: Retro Q3\ntags: [work]\n---\n# Retro\n"
+        );
+        let message = run(&mut app, &["rn", "meta", "get", "retro", "tags"]).unwrap();
+        assert!(
+            matches!(message, Message::MetaValue { value: FieldValue::List(tags), .. } if tags == ["work"])
+        );
+
+        run(&mut app, &["rn", "meta", "unset", "retro", "title"]).unwrap();
+        assert!(run(&mut app, &["rn", "meta", "unset", "retro", "title"]).is_err());
+        run(&mut app, &["rn", "meta", "unset", "retro", "tags"]).unwrap();
+        assert_eq!(app.fs.read_file(&retro).unwrap(), "# Retro\n");
+
+        run(&mut app, &["rn", "undo"]).unwrap();
+        assert_eq!(
+            app.fs.read_file(&retro).unwrap(),
+            "---\ntags: [work]\n---\n# Retro\n"
+        );
+    }
+
+    #[test]
+    fn test_tags() {
+        let mut app = test_app(&["notebook/api", "notebook/web", "notebook/old"]);
+        app.fs
+            .write_file(Path::new("/rn/notebook/web"), "# Web\n#work #done\n")
+            .unwrap();
+        run(&mut app, &["rn", "tag", "add", "api", "work", "backend"]).unwrap();
+        run(&mut app, &["rn", "tag", "add", "old", "done"]).unwrap();
+        assert!(run(&mut app, &["rn", "tag", "add", "api", "a b"]).is_err());
+        assert_eq!(
+            app.fs.read_file(Path::new("/rn/notebook/api")).unwrap(),
+            "---\ntags: [work, backend]\n---\n"
+        );
+
+        let Message::Tags { tags } = run(&mut app, &["rn", "tags"]).unwrap() else {
+            panic!()
+        };
+        let tags: Vec<(String, usize)> = tags.into_iter().map(|tag| (tag.tag, tag.count)).collect();
+        assert_eq!(
+            tags,
+            [
+                ("backend".into(), 1),
+                ("done".into(), 2),
+                ("work".into(), 2)
+            ]
+        );
+
+        let mut list = |args: &[&str]| {
+            let mut notes = names(
+                &mut app,
+                &[["rn", "ls", "--flat"].as_slice(), args].concat(),
+            );
+            notes.sort();
+            notes
+        };
+        assert_eq!(list(&["--tag", "work"]), ["api", "web"]);
+        assert_eq!(list(&["--tag", "work", "--tag", "!done"]), ["api"]);
+        assert_eq!(list(&["--tag", "backend,done"]), ["api", "old", "web"]);
+
+        // inline tags aren't removed
+        assert!(run(&mut app, &["rn", "tag", "rm", "web", "work"]).is_err());
+        run(&mut app, &["rn", "tag", "rm", "old", "done"]).unwrap();
+        assert_eq!(app.fs.read_file(Path::new("/rn/notebook/old")).unwrap(), "");
+    }
+
+    #[test]
+    fn test_front_matter_timestamps() {
+        let mut app = test_app(&["templates/meeting"]);
+        let template = PathBuf::from("/rn/templates/meeting");
+        app.fs
+            .write_file(&template, "---\ntitle: {{name}}\n---\n")
+            .unwrap();
+        run(&mut app, &["rn", "new", "standup", "--template", "meeting"]).unwrap();
+        let standup = PathBuf::from("/rn/notebook/standup");
+        let content = app.fs.read_file(&standup).unwrap();
+        let (front_matter, _) = FrontMatter::parse(&content).unwrap();
+        let created = front_matter.get("created").unwrap();
+        assert_eq!(front_matter.get("updated"), Some(created.clone()));
+
+        // unchanged notes and notes without front matter are left as they are
+        app.fs
+            .write_file(&standup, "---\ncreated: then\n---\n")
+            .unwrap();
+        run(&mut app, &["rn", "open", "standup"]).unwrap();
+        assert_eq!(
+            app.fs.read_file(&standup).unwrap(),
+            "---\ncreated: then\n---\n"
+        );
+        run(&mut app, &["rn", "new", "plain"]).unwrap();
+        app.fs.set_edit("text\n");
+        run(&mut app, &["rn", "open", "plain"]).unwrap();
+        assert_eq!(
+            app.fs.read_file(Path::new("/rn/notebook/plain")).unwrap(),
+            "text\n"
+        );
+
+        app.fs.set_edit("text\n");
+        run(&mut app, &["rn", "open", "standup"]).unwrap();
+        let content = app.fs.read_file(&standup).unwrap();
+        let (front_matter, body) = FrontMatter::parse(&content).unwrap();
+        assert_eq!(
+            front_matter.get("created"),
+            Some(FieldValue::Text("then".into()))
+        );
+        assert!(front_matter.get("updated").is_some());
+        assert_eq!(body, "text\n");
+    }
+
+    #[test]
+    fn test_archive_prune() {
+        let mut app = test_app(&[
+            "archive/todo_01-03-2026-10:00:00",
+            "archive/todo_02-03-2026-10:00:00",
+            "archive/todo_03-03-2026-10:00:00",
+            "archive/other_01-03-2026-10:00:00",
+            "archive/unknown",
+        ]);
+        let message = run(&mut app, &["rn", "archive", "prune"]).unwrap();
+        assert!(
+            matches!(message, Message::PrunedArchive { names, dry_run: false } if names.is_empty())
+        );
+
+        app.config.archive_keep_last = Some(1);
+        let message = run(&mut app, &["rn", "archive", "prune", "--dry-run"]).unwrap();
+        let Message::PrunedArchive {
+            names: pruned,
+            dry_run: true,
+        } = message
+        else {
+            panic!()
+        };
+        assert_eq!(
+            pruned,
+            ["todo_01-03-2026-10:00:00", "todo_02-03-2026-10:00:00"]
+        );
+        assert!(app
+            .fs
+            .is_file(Path::new("/rn/archive/todo_01-03-2026-10:00:00")));
+
+        // the last version of a note is kept however old it is
+        app.config.archive_max_age = Some("1w".into());
+        run(&mut app, &["rn", "archive", "prune"]).unwrap();
+        let archived = names(&mut app, &["rn", "archive", "ls"]);
+        assert_eq!(
+            archived,
+            [
+                "other_01-03-2026-10:00:00",
+                "todo_03-03-2026-10:00:00",
+                "unknown"
+            ]
+        );
+
+        app.config.archive_max_age = Some("forever".into());
+        assert!(run(&mut app, &["rn", "archive", "prune"]).is_err());
+    }
+
+    #[test]
+    fn test_compressed_archive() {
+        let mut app = test_app(&["notebook/log"]);
+        app.config.archive_format = ArchiveFormat::Gzip;
+        let log = PathBuf::from("/rn/notebook/log");
+        app.fs.write_file(&log, "build passed\n").unwrap();
+        let message = run(&mut app, &["rn", "archive", "save", "log"]).unwrap();
+        let Message::ArchivedNote {
+            to: archived_name, ..
+        } = message
+        else {
+            panic!()
+        };
+        let archived_path = PathBuf::from("/rn/archive").join(&archived_name);
+        assert!(archive::is_compressed(
+            &app.fs.read_bytes(&archived_path).unwrap()
+        ));
+
+        let message = run(&mut app, &["rn", "archive", "show", "log"]).unwrap();
+        let Message::NoteContents { notes } = message else {
+            panic!()
+        };
+        let [NoteContent { name, content }] = &notes[..] else {
+            panic!()
+        };
+        assert_eq!((name, content.as_str()), (&archived_name, "build passed\n"));
+        let message = run(&mut app, &["rn", "search", "-a", "passed"]).unwrap();
+        assert!(matches!(message, Message::SearchResults { matches, .. } if matches.len() == 1));
+
+        run(&mut app, &["rn", "archive", "open", "log"]).unwrap();
+        assert!(archive::is_compressed(
+            &app.fs.read_bytes(&archived_path).unwrap()
+        ));
+
+        run(&mut app, &["rn", "archive", "restore", "log"]).unwrap();
+        assert_eq!(app.fs.read_file(&log).unwrap(), "build passed\n");
+    }
+
+    #[test]
+    fn test_archive_compact() {
+        let mut app = test_app(&["archive/a_01-03-2026-10:00:00", "archive/b"]);
+        let a = PathBuf::from("/rn/archive/a_01-03-2026-10:00:00");
+        app.fs.write_file(&a, &"text\n".repeat(100)).unwrap();
+        let message = run(&mut app, &["rn", "archive", "compact"]).unwrap();
+        let Message::CompactedArchive {
+            count: 2,
+            size,
+            compressed_size,
+        } = message
+        else {
+            panic!()
+        };
+        assert_eq!(size, 500);
+        assert!(compressed_size < size);
+        assert!(archive::is_compressed(&app.fs.read_bytes(&a).unwrap()));
+
+        let message = run(&mut app, &["rn", "archive", "compact"]).unwrap();
+        assert!(matches!(
+            message,
+            Message::CompactedArchive {
+                count: 0,
+                size: 0,
+                compressed_size: 0
+            }
+        ));
+        let message = run(&mut app, &["rn", "archive", "restore", "a"]).unwrap();
+        assert!(matches!(message, Message::RestoredNotes { .. }));
+        let restored = PathBuf::from("/rn/notebook/a");
+        assert_eq!(app.fs.read_file(&restored).unwrap(), "text\n".repeat(100));
     }
 }
@@ -0,0 +1,289 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+use anyhow::Result;
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, TimeZone};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+
+/// Time stamp appended to the names of archived notes.
+pub const TIME_STAMP_FORMAT: &str = "%d-%m-%Y-%H:%M:%S";
+
+/// First bytes of gzip data, text notes can't start with them as they aren't
+/// valid UTF-8.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+pub fn is_compressed(content: &[u8]) -> bool {
+    content.starts_with(&GZIP_MAGIC)
+}
+
+pub fn compress(content: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(content)?;
+    Ok(encoder.finish()?)
+}
+
+pub fn decompress(content: &[u8]) -> Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    GzDecoder::new(content).read_to_end(&mut decompressed)?;
+    Ok(decompressed)
+}
+
+/// Text of a note as stored on disk, compressed notes are decompressed.
+pub fn decode(content: Vec<u8>) -> Result<String> {
+    if is_compressed(&content) {
+        return Ok(String::from_utf8(decompress(&content)?)?);
+    }
+    Ok(String::from_utf8(content)?)
+}
+
+/// Where an archived note came from, the notebook is the one of the archive
+/// holding it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ArchiveEntry {
+    /// Name of the note when it was archived.
+    pub original: String,
+    /// Archiving time in seconds since the unix epoch.
+    pub archived: i64,
+}
+
+/// Metadata of the notes in an archive, keyed by their archived name.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ArchiveManifest {
+    pub entries: BTreeMap<String, ArchiveEntry>,
+}
+
+impl ArchiveManifest {
+    /// Metadata of the archived note `name`. For notes archived before the
+    /// metadata was kept, it's derived from the name.
+    pub fn entry(&self, name: &str) -> ArchiveEntry {
+        if let Some(entry) = self.entries.get(name) {
+            return entry.clone();
+        }
+        let (original, archived) = name
+            .rsplit_once('_')
+            .and_then(|(original, time_stamp)| {
+                let time = NaiveDateTime::parse_from_str(time_stamp, TIME_STAMP_FORMAT).ok()?;
+                let time = Local.from_local_datetime(&time).earliest()?;
+                Some((original, time.timestamp()))
+            })
+            .unwrap_or((name, 0));
+        ArchiveEntry {
+            original: original.to_owned(),
+            archived,
+        }
+    }
+
+    /// All versions of the note `original` among the archived notes `names`,
+    /// oldest first.
+    pub fn versions(&self, original: &str, names: &[String]) -> Vec<(String, ArchiveEntry)> {
+        let mut versions: Vec<(String, ArchiveEntry)> = names
+            .iter()
+            .map(|name| (name.clone(), self.entry(name)))
+            .filter(|(_, entry)| entry.original == original)
+            .collect();
+        versions.sort_by(|(a_name, a), (b_name, b)| {
+            a.archived.cmp(&b.archived).then_with(|| a_name.cmp(b_name))
+        });
+        versions
+    }
+}
+
+/// Identifies the day, week or month of a date.
+type Period = fn(NaiveDate) -> (i32, u32);
+
+/// Rules which archived versions of a note are kept when the archive is
+/// pruned. Without any `keep_` rule every version is kept.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Retention {
+    pub keep_last: Option<usize>,
+    /// Keeps the latest version of each of the last N days with versions.
+    pub keep_daily: Option<usize>,
+    pub keep_weekly: Option<usize>,
+    pub keep_monthly: Option<usize>,
+    /// Versions archived before this time are removed, even if a `keep_`
+    /// rule other than `keep_last` keeps them. The newest version is kept
+    /// regardless.
+    pub cutoff: Option<i64>,
+}
+
+impl Retention {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether each of the versions archived at `times`, oldest first, is
+    /// kept.
+    pub fn keep(&self, times: &[i64]) -> Vec<bool> {
+        let has_keep_rules = self.keep_last.is_some()
+            || self.keep_daily.is_some()
+            || self.keep_weekly.is_some()
+            || self.keep_monthly.is_some();
+        let mut versions: Vec<(i64, bool)> =
+            times.iter().map(|time| (*time, !has_keep_rules)).collect();
+        let periods: [(Option<usize>, Period); 3] = [
+            (self.keep_daily, |date| (date.year(), date.ordinal())),
+            (self.keep_weekly, |date| {
+                (date.iso_week().year(), date.iso_week().week())
+            }),
+            (self.keep_monthly, |date| (date.year(), date.month())),
+        ];
+        for (count, period) in periods {
+            let Some(count) = count else {
+                continue;
+            };
+            let mut seen = Vec::new();
+            for (time, kept) in versions.iter_mut().rev() {
+                let key = period(local_date(*time));
+                if seen.contains(&key) {
+                    continue;
+                }
+                if seen.len() == count {
+                    break;
+                }
+                seen.push(key);
+                *kept = true;
+            }
+        }
+        if let Some(cutoff) = self.cutoff {
+            for (time, kept) in &mut versions {
+                if *time < cutoff {
+                    *kept = false;
+                }
+            }
+        }
+        // the last versions survive the cutoff, the newest one always, so
+        // pruning never removes every version of a note
+        let count = self.keep_last.unwrap_or_default().max(1);
+        for (_, kept) in versions.iter_mut().rev().take(count) {
+            *kept = true;
+        }
+        versions.into_iter().map(|(_, kept)| kept).collect()
+    }
+}
+
+fn local_date(time: i64) -> NaiveDate {
+    Local
+        .timestamp_opt(time, 0)
+        .single()
+        .map(|time| time.date_naive())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // tests
+mod tests {
+    use super::*;
+
+    fn entry(original: &str, archived: i64) -> ArchiveEntry {
+        ArchiveEntry {
+            original: original.into(),
+            archived,
+        }
+    }
+
+    #[test]
+    fn test_entry() {
+        let manifest = ArchiveManifest {
+            entries: BTreeMap::from([("plan_v2".into(), entry("road_map", 10))]),
+        };
+        assert_eq!(manifest.entry("plan_v2"), entry("road_map", 10));
+
+        let legacy = manifest.entry("meeting_notes_01-02-2026-10:00:00");
+        assert_eq!(legacy.original, "meeting_notes");
+        assert_ne!(legacy.archived, 0);
+        assert_eq!(manifest.entry("no_stamp"), entry("no_stamp", 0));
+    }
+
+    #[test]
+    fn test_versions() {
+        let manifest = ArchiveManifest {
+            entries: BTreeMap::from([
+                ("b".into(), entry("todo", 20)),
+                ("a".into(), entry("todo", 30)),
+                ("c".into(), entry("other", 10)),
+            ]),
+        };
+        let names = ["a".into(), "b".into(), "c".into()];
+        let versions: Vec<String> = manifest
+            .versions("todo", &names)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(versions, ["b", "a"]);
+    }
+
+    fn time(day: u32, hour: u32) -> i64 {
+        Local
+            .with_ymd_and_hms(2026, 3, day, hour, 0, 0)
+            .single()
+            .map_or(0, |time| time.timestamp())
+    }
+
+    #[test]
+    fn test_retention() {
+        // two versions on the 2nd, one on the 3rd and one on the 10th
+        let times = [time(2, 9), time(2, 17), time(3, 9), time(10, 9)];
+        assert_eq!(Retention::default().keep(&times), [true; 4]);
+
+        let keep_last = Retention {
+            keep_last: Some(2),
+            ..Retention::default()
+        };
+        assert_eq!(keep_last.keep(&times), [false, false, true, true]);
+
+        let keep_daily = Retention {
+            keep_daily: Some(3),
+            ..Retention::default()
+        };
+        assert_eq!(keep_daily.keep(&times), [false, true, true, true]);
+
+        // the 2nd and 3rd of march 2026 are in the same week
+        let keep_weekly = Retention {
+            keep_weekly: Some(5),
+            ..Retention::default()
+        };
+        assert_eq!(keep_weekly.keep(&times), [false, false, true, true]);
+
+        let keep_monthly = Retention {
+            keep_monthly: Some(1),
+            cutoff: Some(time(3, 0)),
+            ..Retention::default()
+        };
+        assert_eq!(keep_monthly.keep(&times), [false, false, false, true]);
+
+        let cutoff = Retention {
+            cutoff: Some(time(3, 0)),
+            ..Retention::default()
+        };
+        assert_eq!(cutoff.keep(&times), [false, false, true, true]);
+
+        // every version is older than the cutoff
+        let cutoff = Retention {
+            keep_last: Some(1),
+            keep_daily: Some(3),
+            cutoff: Some(time(20, 0)),
+            ..Retention::default()
+        };
+        assert_eq!(cutoff.keep(&times), [false, false, false, true]);
+
+        let max_age_only = Retention {
+            cutoff: Some(time(20, 0)),
+            ..Retention::default()
+        };
+        assert_eq!(max_age_only.keep(&times), [false, false, false, true]);
+        assert!(max_age_only.keep(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_compression() {
+        let content = "line\n".repeat(100);
+        let compressed = compress(content.as_bytes()).unwrap();
+        assert!(is_compressed(&compressed));
+        assert!(compressed.len() < content.len());
+        assert!(!is_compressed(content.as_bytes()));
+        assert_eq!(decompress(&compressed).unwrap(), content.as_bytes());
+        assert_eq!(decode(compressed).unwrap(), content);
+        assert_eq!(decode(content.clone().into_bytes()).unwrap(), content);
+    }
+}
@@ -1,3 +1,6 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
 use clap::Subcommand as ClapSubcommand;
 use clap::{Args, Parser, ValueEnum};
 
@@ -8,6 +11,26 @@ use clap::{Args, Parser, ValueEnum};
 #[command(disable_help_subcommand = true)]
 #[command(flatten_help = true)]
 pub struct Cli {
+    #[arg(help = "Root directory of rn, overrides the `root_dir` config value")]
+    #[arg(long, global = true, env = "RN_ROOT", value_name = "DIR")]
+    pub root: Option<PathBuf>,
+
+    #[arg(help = "Notebook to use instead of the current one")]
+    #[arg(long, global = true, value_name = "NAME")]
+    pub notebook: Option<String>,
+
+    #[arg(help = "Only accept exact note names, no prefixes or fuzzy matches")]
+    #[arg(long, global = true)]
+    pub exact: bool,
+
+    #[arg(help = "Output format, json and tsv are meant for scripts")]
+    #[arg(long, global = true, value_enum, default_value_t)]
+    pub format: OutputFormat,
+
+    #[arg(help = "Don't print results, errors are still printed to stderr")]
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
     #[command(subcommand)]
     pub subcommand: Subcommand,
 }
@@ -17,16 +40,40 @@ pub enum Subcommand {
     #[command(about = "Create a new note")]
     New(NewArgs),
 
+    #[command(about = "Append text to a note, creating it if needed")]
+    Append(AppendArgs),
+
     #[command(about = "Open a note")]
     Open(OpenArgs),
 
+    #[command(about = "Print notes without opening an editor")]
+    #[clap(visible_alias = "cat")]
+    Show(ShowArgs),
+
+    #[command(about = "Rename or move a note")]
+    #[clap(visible_alias = "mv")]
+    Move(MoveArgs),
+
+    #[command(about = "Copy a note to a new note")]
+    #[clap(visible_alias = "cp")]
+    Copy(CopyArgs),
+
     #[command(about = "Delete a note")]
     #[clap(visible_alias = "rm")]
     Remove(RemoveArgs),
 
+    #[command(about = "Open the journal note of today")]
+    Today(JournalArgs),
+
+    #[command(about = "Open the journal note of yesterday")]
+    Yesterday(JournalArgs),
+
+    #[command(about = "Open the journal note of a date or list journal notes")]
+    Day(DayArgs),
+
     #[command(about = "List existing notes")]
     #[clap(visible_alias = "ls")]
-    List,
+    List(ListArgs),
 
     #[command(about = "Access config via cli")]
     Config(ConfigArgs),
@@ -36,6 +83,42 @@ pub enum Subcommand {
 
     #[command(about = "View and manage archive")]
     Archive(ArchiveArgs),
+
+    #[command(about = "Save a version of a note in the archive and keep the note")]
+    Snapshot(SnapshotArgs),
+
+    #[command(about = "View and edit the front matter of a note")]
+    Meta(MetaArgs),
+
+    #[command(about = "Add or remove tags of a note")]
+    Tag(TagArgs),
+
+    #[command(about = "List all tags with the number of notes using them")]
+    Tags,
+
+    #[command(about = "Manage notebooks")]
+    Notebook(NotebookArgs),
+
+    #[command(about = "Manage note templates")]
+    Template(TemplateArgs),
+
+    #[command(about = "View and manage deleted notes")]
+    Trash(TrashArgs),
+
+    #[command(about = "Undo the last operation which changed notes")]
+    Undo,
+
+    #[command(about = "Redo the last undone operation")]
+    Redo,
+
+    #[command(about = "List the operations which can be undone")]
+    History,
+
+    #[command(about = "Search the content of notes")]
+    Search(SearchArgs),
+
+    #[command(about = "Manage the search index")]
+    Index(IndexArgs),
 }
 
 #[derive(Args)]
@@ -43,22 +126,204 @@ pub struct NewArgs {
     #[arg(help = "Name of the note to be created")]
     #[arg(value_parser=non_empty_trimmed)]
     pub name: String,
+
+    #[arg(help = "Text the note starts with")]
+    #[arg(short, long, conflicts_with = "stdin")]
+    pub message: Option<String>,
+
+    #[arg(help = "Read the text the note starts with from stdin")]
+    #[arg(long)]
+    pub stdin: bool,
+
+    #[arg(help = "Put a line with the current time in front of the text")]
+    #[arg(long)]
+    pub stamp: bool,
+
+    #[arg(help = "Template the note is created from, overrides `default_template`")]
+    #[arg(short, long)]
+    pub template: Option<String>,
+
+    #[arg(help = "Don't use the `default_template`")]
+    #[arg(long, conflicts_with = "template")]
+    pub no_template: bool,
+
+    #[arg(help = "Value of a custom template variable")]
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = key_value)]
+    pub vars: Vec<(String, String)>,
 }
 
 #[derive(Args)]
-pub struct OpenArgs {
-    #[arg(help = "Name of the note to open")]
+pub struct AppendArgs {
+    #[arg(help = "Exact name of the note to append to, it's created if it doesn't exist")]
+    #[arg(value_parser=non_empty_trimmed)]
     pub name: String,
 
+    #[arg(help = "Text to append, read from stdin if omitted")]
+    #[arg(short, long)]
+    pub message: Option<String>,
+
+    #[arg(help = "Put a line with the current time in front of the text")]
+    #[arg(long)]
+    pub stamp: bool,
+}
+
+#[derive(Args)]
+pub struct OpenArgs {
+    #[arg(help = "Name of the note to open, pick it interactively if omitted")]
+    pub name: Option<String>,
+
     #[arg(help = "Editor command used to open the note")]
     #[arg(short, long)]
     pub editor: Option<String>,
 }
 
 #[derive(Args)]
-pub struct RemoveArgs {
-    #[arg(help = "Name of the note to be deleted")]
+pub struct ShowArgs {
+    #[arg(help = "Names of the notes to print")]
+    #[arg(required = true)]
+    pub names: Vec<String>,
+
+    #[arg(help = "Only print the lines START:END, both are inclusive and optional")]
+    #[arg(short, long, value_name = "START:END", value_parser = line_range)]
+    #[arg(conflicts_with_all = ["head", "tail"])]
+    pub lines: Option<LineRange>,
+
+    #[arg(help = "Only print the first N lines")]
+    #[arg(long, value_name = "N", conflicts_with = "tail")]
+    pub head: Option<usize>,
+
+    #[arg(help = "Only print the last N lines")]
+    #[arg(long, value_name = "N")]
+    pub tail: Option<usize>,
+}
+
+/// Line numbers start at 1, missing bounds are open.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineRange {
+    pub start: Option<usize>,
+    pub end: Option<usize>,
+}
+
+#[derive(Args)]
+pub struct MoveArgs {
+    #[arg(help = "Name of the note to move")]
     pub name: String,
+
+    #[arg(help = "New name of the note, a trailing / moves it into that folder")]
+    #[arg(value_parser=non_empty_trimmed)]
+    pub new_name: String,
+
+    #[arg(help = "Notebook to move the note into")]
+    #[arg(long, value_name = "NOTEBOOK")]
+    pub to_notebook: Option<String>,
+
+    #[arg(help = "Overwrite an existing note with the new name")]
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Args)]
+pub struct CopyArgs {
+    #[arg(help = "Name of the note to copy")]
+    pub source: String,
+
+    #[arg(help = "Name of the new note, a trailing / copies it into that folder")]
+    #[arg(value_parser=non_empty_trimmed)]
+    pub destination: String,
+
+    #[arg(help = "Copy an archived note")]
+    #[arg(short, long)]
+    pub archive: bool,
+
+    #[arg(help = "Only copy the front matter of the note")]
+    #[arg(long)]
+    pub only_header: bool,
+}
+
+#[derive(Args)]
+pub struct RemoveArgs {
+    #[arg(help = "Name of the note to be deleted, pick notes interactively if omitted")]
+    pub name: Option<String>,
+
+    #[arg(help = "Delete the note permanently instead of moving it to the trash")]
+    #[arg(long)]
+    pub permanent: bool,
+}
+
+#[derive(Args)]
+pub struct JournalArgs {
+    #[arg(help = "Template a new journal note is created from, overrides `journal_template`")]
+    #[arg(short, long)]
+    pub template: Option<String>,
+
+    #[arg(help = "Editor command used to open the note")]
+    #[arg(short, long)]
+    pub editor: Option<String>,
+}
+
+#[derive(Args)]
+pub struct DayArgs {
+    #[arg(help = "Date of the journal note, as YYYY-MM-DD or in the `journal_date_format`")]
+    #[arg(required_unless_present = "list", conflicts_with = "list")]
+    pub date: Option<String>,
+
+    #[arg(help = "List the journal notes instead of opening one")]
+    #[arg(short, long)]
+    pub list: bool,
+
+    #[arg(help = "Only list journal notes from this date on")]
+    #[arg(long, requires = "list", conflicts_with = "date", value_name = "DATE")]
+    pub from: Option<String>,
+
+    #[arg(help = "Only list journal notes up to this date")]
+    #[arg(long, requires = "list", conflicts_with = "date", value_name = "DATE")]
+    pub to: Option<String>,
+
+    #[command(flatten)]
+    pub journal: JournalArgs,
+}
+
+#[derive(Args)]
+#[allow(clippy::struct_excessive_bools)] // independent flags
+pub struct ListArgs {
+    #[arg(help = "Only list notes whose full name matches the glob pattern")]
+    pub pattern: Option<String>,
+
+    #[arg(help = "Interpret the pattern as regular expression matching part of the name")]
+    #[arg(long, requires = "pattern")]
+    pub regex: bool,
+
+    #[arg(help = "List full note paths instead of a folder tree")]
+    #[arg(long)]
+    pub flat: bool,
+
+    #[arg(help = "Order of the notes, implies --flat")]
+    #[arg(long, value_enum, value_name = "KEY")]
+    pub sort: Option<SortKey>,
+
+    #[arg(help = "Reverse the order, implies --flat")]
+    #[arg(short, long)]
+    pub reverse: bool,
+
+    #[arg(help = "Show size, line count and modification time, implies --flat")]
+    #[arg(short, long)]
+    pub long: bool,
+
+    #[arg(help = "Only list notes modified on or after this date (YYYY-MM-DD)")]
+    #[arg(long, value_name = "DATE")]
+    pub since: Option<String>,
+
+    #[arg(help = "Only list notes modified on or before this date (YYYY-MM-DD)")]
+    #[arg(long, value_name = "DATE")]
+    pub until: Option<String>,
+
+    #[arg(help = "Only list the first N notes, implies --flat")]
+    #[arg(long, value_name = "N")]
+    pub limit: Option<usize>,
+
+    #[arg(help = "Only list notes with the tag, `!TAG` for notes without it, `A,B` for either")]
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
 }
 
 #[derive(Args)]
@@ -107,6 +372,24 @@ pub enum Shell {
     Zsh,
 }
 
+/// Names are sorted alphabetically, the other keys newest or largest first.
+#[derive(ValueEnum, Clone, Copy, Default, PartialEq, Debug)]
+pub enum SortKey {
+    #[default]
+    Name,
+    Modified,
+    Created,
+    Size,
+}
+
+#[derive(ValueEnum, Clone, Copy, Default, PartialEq, Debug)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Tsv,
+}
+
 #[derive(Args)]
 pub struct ArchiveArgs {
     #[command(subcommand)]
@@ -125,12 +408,113 @@ pub enum ArchiveSubcommand {
     #[command(about = "Open a archived note")]
     Open(ArchiveOpenArgs),
 
+    #[command(about = "Print archived notes without opening an editor")]
+    #[clap(visible_alias = "cat")]
+    Show(ShowArgs),
+
     #[command(about = "Restore a note from the archive")]
     Restore(ArchiveRestoreArgs),
 
-    #[command(about = "Delete a archived note permanently")]
+    #[command(about = "Rename or move a archived note")]
+    #[clap(visible_alias = "mv")]
+    Move(MoveArgs),
+
+    #[command(about = "Delete a archived note")]
     #[clap(visible_alias = "rm")]
     Remove(ArchiveRemoveArgs),
+
+    #[command(about = "List the archived versions of a note")]
+    History(ArchiveHistoryArgs),
+
+    #[command(about = "Show the changes between archived versions of a note")]
+    Diff(ArchiveDiffArgs),
+
+    #[command(about = "Delete archived versions according to the retention config")]
+    Prune(ArchivePruneArgs),
+
+    #[command(about = "Compress the archived notes which are stored as plain text")]
+    Compact,
+}
+
+#[derive(Args)]
+pub struct SnapshotArgs {
+    #[arg(help = "Name of the note to snapshot")]
+    pub name: String,
+}
+
+#[derive(Args)]
+pub struct MetaArgs {
+    #[command(subcommand)]
+    pub subcommand: MetaSubcommand,
+}
+
+#[derive(ClapSubcommand)]
+pub enum MetaSubcommand {
+    #[command(about = "Print the value of a front matter field")]
+    Get(MetaGetArgs),
+
+    #[command(about = "Set a front matter field, adding front matter if needed")]
+    Set(MetaSetArgs),
+
+    #[command(about = "Remove a front matter field")]
+    Unset(MetaUnsetArgs),
+}
+
+#[derive(Args)]
+pub struct MetaGetArgs {
+    #[arg(help = "Name of the note")]
+    pub name: String,
+
+    #[arg(help = "Name of the field")]
+    pub key: String,
+}
+
+#[derive(Args)]
+pub struct MetaSetArgs {
+    #[arg(help = "Name of the note")]
+    pub name: String,
+
+    #[arg(help = "Name of the field")]
+    pub key: String,
+
+    #[arg(help = "Value of the field, multiple values make a list")]
+    #[arg(required = true)]
+    pub values: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct MetaUnsetArgs {
+    #[arg(help = "Name of the note")]
+    pub name: String,
+
+    #[arg(help = "Name of the field")]
+    pub key: String,
+}
+
+#[derive(Args)]
+pub struct TagArgs {
+    #[command(subcommand)]
+    pub subcommand: TagSubcommand,
+}
+
+#[derive(ClapSubcommand)]
+pub enum TagSubcommand {
+    #[command(about = "Add tags to the front matter of a note")]
+    Add(TagEditArgs),
+
+    #[command(about = "Remove tags from the front matter of a note")]
+    #[clap(visible_alias = "rm")]
+    Remove(TagEditArgs),
+}
+
+#[derive(Args)]
+pub struct TagEditArgs {
+    #[arg(help = "Name of the note")]
+    pub name: String,
+
+    #[arg(help = "Tags to add or remove")]
+    #[arg(required = true)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Args)]
@@ -141,8 +525,8 @@ pub struct ArchiveSaveArgs {
 
 #[derive(Args)]
 pub struct ArchiveOpenArgs {
-    #[arg(help = "Name of the note to open")]
-    pub name: String,
+    #[arg(help = "Name of the note to open, pick it interactively if omitted")]
+    pub name: Option<String>,
 
     #[arg(help = "Editor command used to open the note")]
     #[arg(short, long)]
@@ -150,18 +534,239 @@ pub struct ArchiveOpenArgs {
 }
 
 #[derive(Args)]
-pub struct ArchiveRestoreArgs {
-    #[arg(help = "Name of the note to restore from archive")]
-    pub archive_name: String,
+pub struct ArchiveRestoreArgs {
+    #[arg(help = "Names of the notes to restore from archive")]
+    #[arg(required = true)]
+    pub archive_names: Vec<String>,
+
+    #[arg(help = "New name of the note after its restored, only for a single note")]
+    #[arg(short, long)]
+    pub new_name: Option<String>,
+
+    #[arg(help = "Keep the archived copy of the notes")]
+    #[arg(short, long)]
+    pub keep: bool,
+}
+
+#[derive(Args)]
+pub struct ArchiveRemoveArgs {
+    #[arg(help = "Name of the note to delete from archive")]
+    pub name: String,
+
+    #[arg(help = "Delete the note permanently instead of moving it to the trash")]
+    #[arg(long)]
+    pub permanent: bool,
+}
+
+#[derive(Args)]
+pub struct ArchiveHistoryArgs {
+    #[arg(help = "Name the note had when it was archived")]
+    pub name: String,
+}
+
+#[derive(Args)]
+pub struct ArchiveDiffArgs {
+    #[arg(help = "Name the note had when it was archived")]
+    pub name: String,
+
+    #[arg(help = "Version to compare, defaults to the one before TO")]
+    pub from: Option<usize>,
+
+    #[arg(help = "Version to compare with, defaults to the latest one")]
+    pub to: Option<usize>,
+}
+
+#[derive(Args)]
+pub struct ArchivePruneArgs {
+    #[arg(help = "Only list the versions which would be deleted")]
+    #[arg(short = 'n', long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args)]
+#[allow(clippy::struct_excessive_bools)] // independent flags
+pub struct SearchArgs {
+    #[arg(help = "Text to search for")]
+    pub query: String,
+
+    #[arg(help = "Ignore case distinctions")]
+    #[arg(short, long)]
+    pub ignore_case: bool,
+
+    #[arg(help = "Only match whole words")]
+    #[arg(short, long)]
+    pub word: bool,
+
+    #[arg(help = "Interpret the query as regular expression")]
+    #[arg(short, long)]
+    pub regex: bool,
+
+    #[arg(help = "Also search archived notes")]
+    #[arg(short, long)]
+    pub archive: bool,
+
+    #[arg(help = "Order notes by relevance instead of name")]
+    #[arg(long, conflicts_with = "regex")]
+    pub ranked: bool,
+}
+
+#[derive(Args)]
+pub struct IndexArgs {
+    #[command(subcommand)]
+    pub subcommand: IndexSubcommand,
+}
+
+#[derive(ClapSubcommand)]
+pub enum IndexSubcommand {
+    #[command(about = "Rebuild the search index from scratch")]
+    Rebuild,
+}
+
+#[derive(Args)]
+pub struct TemplateArgs {
+    #[command(subcommand)]
+    pub subcommand: TemplateSubcommand,
+}
+
+#[derive(ClapSubcommand)]
+pub enum TemplateSubcommand {
+    #[command(about = "Create a new template and open it")]
+    New(TemplateNewArgs),
+
+    #[command(about = "List all templates")]
+    #[clap(visible_alias = "ls")]
+    List,
+
+    #[command(about = "Open a template")]
+    Edit(TemplateEditArgs),
+
+    #[command(about = "Delete a template")]
+    #[clap(visible_alias = "rm")]
+    Remove(TemplateRemoveArgs),
+}
+
+#[derive(Args)]
+pub struct TemplateNewArgs {
+    #[arg(help = "Name of the template to be created")]
+    #[arg(value_parser=non_empty_trimmed)]
+    pub name: String,
+
+    #[arg(help = "Editor command used to open the template")]
+    #[arg(short, long)]
+    pub editor: Option<String>,
+}
+
+#[derive(Args)]
+pub struct TemplateEditArgs {
+    #[arg(help = "Name of the template to open")]
+    pub name: String,
+
+    #[arg(help = "Editor command used to open the template")]
+    #[arg(short, long)]
+    pub editor: Option<String>,
+}
+
+#[derive(Args)]
+pub struct TemplateRemoveArgs {
+    #[arg(help = "Name of the template to be deleted")]
+    pub name: String,
+}
+
+#[derive(Args)]
+pub struct TrashArgs {
+    #[command(subcommand)]
+    pub subcommand: TrashSubcommand,
+}
+
+#[derive(ClapSubcommand)]
+pub enum TrashSubcommand {
+    #[command(about = "List all deleted notes")]
+    #[clap(visible_alias = "ls")]
+    List,
+
+    #[command(about = "Restore a note from the trash")]
+    Restore(TrashRestoreArgs),
+
+    #[command(about = "Delete all notes in the trash permanently")]
+    Empty,
+
+    #[command(about = "Delete old notes in the trash permanently")]
+    Purge(TrashPurgeArgs),
+}
+
+#[derive(Args)]
+pub struct TrashRestoreArgs {
+    #[arg(help = "Id or original name of the deleted note")]
+    pub entry: String,
+
+    #[arg(help = "New name of the note after its restored")]
+    #[arg(short, long)]
+    pub new_name: Option<String>,
+}
+
+#[derive(Args)]
+pub struct TrashPurgeArgs {
+    #[arg(help = "Only delete notes which were deleted longer ago, e.g. 30d, 12h or 2w")]
+    #[arg(long, value_name = "AGE", value_parser = age)]
+    pub older_than: Duration,
+}
+
+#[derive(Args)]
+pub struct NotebookArgs {
+    #[command(subcommand)]
+    pub subcommand: NotebookSubcommand,
+}
+
+#[derive(ClapSubcommand)]
+pub enum NotebookSubcommand {
+    #[command(about = "Create a new notebook")]
+    Create(NotebookCreateArgs),
+
+    #[command(about = "List all notebooks")]
+    #[clap(visible_alias = "ls")]
+    List,
+
+    #[command(about = "Delete a notebook")]
+    #[clap(visible_alias = "rm")]
+    Remove(NotebookRemoveArgs),
+
+    #[command(about = "Rename a notebook")]
+    Rename(NotebookRenameArgs),
+
+    #[command(about = "Set the current notebook")]
+    Use(NotebookUseArgs),
+}
+
+#[derive(Args)]
+pub struct NotebookCreateArgs {
+    #[arg(help = "Name of the notebook to be created")]
+    #[arg(value_parser=non_empty_trimmed)]
+    pub name: String,
+}
+
+#[derive(Args)]
+pub struct NotebookRemoveArgs {
+    #[arg(help = "Name of the notebook to be deleted")]
+    pub name: String,
+
+    #[arg(help = "Delete the notebook even if it contains notes")]
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Args)]
+pub struct NotebookRenameArgs {
+    #[arg(help = "Name of the notebook to rename")]
+    pub name: String,
 
-    #[arg(help = "New name of the note after its restored")]
-    #[arg(short, long)]
-    pub new_name: Option<String>,
+    #[arg(help = "New name of the notebook")]
+    #[arg(value_parser=non_empty_trimmed)]
+    pub new_name: String,
 }
 
 #[derive(Args)]
-pub struct ArchiveRemoveArgs {
-    #[arg(help = "Name of the note to delete from archive")]
+pub struct NotebookUseArgs {
+    #[arg(help = "Name of the notebook to use")]
     pub name: String,
 }
 
@@ -174,6 +779,64 @@ fn non_empty_trimmed(s: &str) -> Result<String, String> {
     }
 }
 
+fn key_value(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err("Expected KEY=VALUE".to_string()),
+    }
+}
+
+pub(crate) fn age(s: &str) -> Result<Duration, String> {
+    const SECONDS_PER_UNIT: [(char, u64); 5] = [
+        ('s', 1),
+        ('m', 60),
+        ('h', 60 * 60),
+        ('d', 24 * 60 * 60),
+        ('w', 7 * 24 * 60 * 60),
+    ];
+    let error = || "Expected a number followed by s, m, h, d or w, e.g. 30d".to_string();
+    let unit = s.chars().last().ok_or_else(error)?;
+    let (_, seconds_per_unit) = SECONDS_PER_UNIT
+        .iter()
+        .find(|(u, _)| *u == unit)
+        .ok_or_else(error)?;
+    let count: u64 = s
+        .strip_suffix(unit)
+        .and_then(|count| count.parse().ok())
+        .ok_or_else(error)?;
+    count
+        .checked_mul(*seconds_per_unit)
+        .map(Duration::from_secs)
+        .ok_or_else(error)
+}
+
+fn line_range(s: &str) -> Result<LineRange, String> {
+    let parse_bound = |bound: &str| -> Result<Option<usize>, String> {
+        if bound.is_empty() {
+            return Ok(None);
+        }
+        match bound.parse::<usize>() {
+            Ok(0) | Err(_) => Err(format!("\"{bound}\" is no line number")),
+            Ok(line) => Ok(Some(line)),
+        }
+    };
+    let Some((start, end)) = s.split_once(':') else {
+        return Err("Expected a range START:END".to_string());
+    };
+    let range = LineRange {
+        start: parse_bound(start)?,
+        end: parse_bound(end)?,
+    };
+    if let (Some(start), Some(end)) = (range.start, range.end) {
+        if start > end {
+            return Err("START must not be greater than END".to_string());
+        }
+    }
+    Ok(range)
+}
+
 #[cfg(test)]
 #[allow(clippy::panic)] // tests
 #[allow(clippy::unwrap_used)] // tests
@@ -195,6 +858,20 @@ mod tests {
         assert!(Cli::try_parse_from(["rn", "--test"]).is_err());
     }
 
+    #[test]
+    fn test_root() {
+        let cli = Cli::parse_from(["rn", "list"]);
+        assert_eq!(cli.root, None);
+
+        let cli = Cli::parse_from(["rn", "--root", "/tmp/notes", "list"]);
+        assert_eq!(cli.root, Some(PathBuf::from("/tmp/notes")));
+
+        let cli = Cli::parse_from(["rn", "list", "--root", "/tmp/notes"]);
+        assert_eq!(cli.root, Some(PathBuf::from("/tmp/notes")));
+
+        assert!(Cli::try_parse_from(["rn", "--root"]).is_err());
+    }
+
     #[test]
     fn test_new_no_name() {
         assert!(Cli::try_parse_from(["rn", "new"]).is_err());
@@ -205,6 +882,15 @@ mod tests {
         assert!(Cli::try_parse_from(["rn", "new", "\t"]).is_err());
     }
 
+    #[test]
+    fn test_new_nested() {
+        let cli = Cli::parse_from(["rn", "new", "projects/api/design"]);
+        let Subcommand::New(args) = cli.subcommand else {
+            panic!()
+        };
+        assert_eq!(args.name, "projects/api/design");
+    }
+
     #[test]
     fn test_new_multiple_names() {
         assert!(Cli::try_parse_from(["rn", "new", "a", "b"]).is_err());
@@ -219,6 +905,87 @@ mod tests {
         assert_eq!(args.name, "my_note");
     }
 
+    #[test]
+    fn test_new_with_content() {
+        let cli = Cli::parse_from(["rn", "new", "my_note", "-m", "text", "--stamp"]);
+        let Subcommand::New(args) = cli.subcommand else {
+            panic!()
+        };
+        assert_eq!(args.message.unwrap(), "text");
+        assert!(!args.stdin);
+        assert!(args.stamp);
+
+        assert!(Cli::try_parse_from(["rn", "new", "my_note", "-m", "text", "--stdin"]).is_err());
+    }
+
+    #[test]
+    fn test_new_with_template() {
+        let cli = Cli::parse_from([
+            "rn", "new", "my_note", "-t", "meeting", "--var", "room=A 1", "--var", "who=me",
+        ]);
+        let Subcommand::New(args) = cli.subcommand else {
+            panic!()
+        };
+        assert_eq!(args.template.unwrap(), "meeting");
+        assert_eq!(
+            args.vars,
+            [
+                ("room".to_string(), "A 1".to_string()),
+                ("who".to_string(), "me".to_string())
+            ]
+        );
+        assert!(!args.no_template);
+
+        assert!(Cli::try_parse_from(["rn", "new", "a", "--var", "room"]).is_err());
+        assert!(Cli::try_parse_from(["rn", "new", "a", "--var", "=x"]).is_err());
+        assert!(Cli::try_parse_from(["rn", "new", "a", "-t", "x", "--no-template"]).is_err());
+    }
+
+    #[test]
+    fn test_template() {
+        assert!(Cli::try_parse_from(["rn", "template"]).is_err());
+        assert!(Cli::try_parse_from(["rn", "template", "new"]).is_err());
+
+        let cli = Cli::parse_from(["rn", "template", "edit", "meeting", "-e", "vi"]);
+        let Subcommand::Template(template_args) = cli.subcommand else {
+            panic!()
+        };
+        let TemplateSubcommand::Edit(edit_args) = template_args.subcommand else {
+            panic!()
+        };
+        assert_eq!(edit_args.name, "meeting");
+        assert_eq!(edit_args.editor.unwrap(), "vi");
+
+        let cli = Cli::parse_from(["rn", "template", "rm", "meeting"]);
+        let Subcommand::Template(template_args) = cli.subcommand else {
+            panic!()
+        };
+        assert!(matches!(
+            template_args.subcommand,
+            TemplateSubcommand::Remove(_)
+        ));
+    }
+
+    #[test]
+    fn test_append() {
+        assert!(Cli::try_parse_from(["rn", "append"]).is_err());
+
+        let cli = Cli::parse_from(["rn", "append", "log"]);
+        let Subcommand::Append(args) = cli.subcommand else {
+            panic!()
+        };
+        assert_eq!(args.name, "log");
+        assert_eq!(args.message, None);
+        assert!(!args.stamp);
+
+        let cli = Cli::parse_from(["rn", "append", "log", "--message", "text", "--stamp"]);
+        let Subcommand::Append(args) = cli.subcommand else {
+            panic!()
+        };
+        assert_eq!(args.message.unwrap(), "text");
+        assert!(args.stamp);
+    }
+
     #[test]
     fn test_open_multiple_names() {
         assert!(Cli::try_parse_from(["rn", "open", "a", "b"]).is_err());
@@ -230,7 +997,16 @@ mod tests {
         let Subcommand::Open(args) = cli.subcommand else {
             panic!()
         };
-        assert_eq!(args.name, "my_note");
+        assert_eq!(args.name.unwrap(), "my_note");
+    }
+
+    #[test]
+    fn test_open_no_name() {
+        let cli = Cli::parse_from(["rn", "open"]);
+        let Subcommand::Open(args) = cli.subcommand else {
+            panic!()
+        };
+        assert_eq!(args.name, None);
     }
 
     #[test]
@@ -239,14 +1015,14 @@ mod tests {
         let Subcommand::Open(args) = cli.subcommand else {
             panic!()
         };
-        assert_eq!(args.name, "my_note");
+        assert_eq!(args.name.unwrap(), "my_note");
         assert_eq!(args.editor.unwrap(), "nvim");
 
         let cli = Cli::parse_from(["rn", "open", "my_note", "--editor", "nvim"]);
         let Subcommand::Open(args) = cli.subcommand else {
             panic!()
         };
-        assert_eq!(args.name, "my_note");
+        assert_eq!(args.name.unwrap(), "my_note");
         assert_eq!(args.editor.unwrap(), "nvim");
     }
 
@@ -261,23 +1037,248 @@ mod tests {
         let Subcommand::Remove(args) = cli.subcommand else {
             panic!()
         };
-        assert_eq!(&args.name, "my_note");
+        assert_eq!(args.name.unwrap(), "my_note");
         let cli = Cli::parse_from(["rn", "rm", "my_note"]);
         let Subcommand::Remove(args) = cli.subcommand else {
             panic!()
         };
-        assert_eq!(&args.name, "my_note");
+        assert_eq!(args.name.unwrap(), "my_note");
+        let cli = Cli::parse_from(["rn", "rm"]);
+        let Subcommand::Remove(args) = cli.subcommand else {
+            panic!()
+        };
+        assert_eq!(args.name, None);
+    }
+
+    #[test]
+    fn test_show() {
+        assert!(Cli::try_parse_from(["rn", "show"]).is_err());
+
+        let cli = Cli::parse_from(["rn", "cat", "a", "b"]);
+        let Subcommand::Show(args) = cli.subcommand else {
+            panic!()
+        };
+        assert_eq!(args.names, ["a", "b"]);
+        assert_eq!(args.lines, None);
+
+        let cli = Cli::parse_from(["rn", "show", "a", "--head", "3"]);
+        let Subcommand::Show(args) = cli.subcommand else {
+            panic!()
+        };
+        assert_eq!(args.head, Some(3));
+        assert!(Cli::try_parse_from(["rn", "show", "a", "--head", "3", "--tail", "3"]).is_err());
+        assert!(Cli::try_parse_from(["rn", "show", "a", "--lines", "1:2", "--tail", "3"]).is_err());
+    }
+
+    #[test]
+    fn test_show_lines() {
+        let lines = |range: &str| {
+            let Subcommand::Show(args) =
+                Cli::parse_from(["rn", "show", "a", "-l", range]).subcommand
+            else {
+                panic!()
+            };
+            args.lines.unwrap()
+        };
+        assert_eq!(
+            lines("2:5"),
+            LineRange {
+                start: Some(2),
+                end: Some(5)
+            }
+        );
+        assert_eq!(
+            lines(":5"),
+            LineRange {
+                start: None,
+                end: Some(5)
+            }
+        );
+        assert_eq!(
+            lines("2:"),
+            LineRange {
+                start: Some(2),
+                end: None
+            }
+        );
+        for invalid in ["5", "0:2", "5:2", "a:b"] {
+            assert!(Cli::try_parse_from(["rn", "show", "a", "-l", invalid]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_journal() {
+        let cli = Cli::parse_from(["rn", "today", "-t", "daily"]);
+        let Subcommand::Today(args) = cli.subcommand else {
+            panic!()
+        };
+        assert_eq!(args.template.unwrap(), "daily");
+        assert!(matches!(
+            Cli::parse_from(["rn", "yesterday"]).subcommand,
+            Subcommand::Yesterday(_)
+        ));
+
+        assert!(Cli::try_parse_from(["rn", "day"]).is_err());
+        let cli = Cli::parse_from(["rn", "day", "2026-10-18"]);
+        let Subcommand::Day(args) = cli.subcommand else {
+            panic!()
+        };
+        assert_eq!(args.date.unwrap(), "2026-10-18");
+        assert!(!args.list);
+
+        let cli = Cli::parse_from(["rn", "day", "--list", "--from", "2026-10-01"]);
+        let Subcommand::Day(args) = cli.subcommand else {
+            panic!()
+        };
+        assert!(args.list);
+        assert_eq!(args.from.unwrap(), "2026-10-01");
+        assert_eq!(args.to, None);
+
+        assert!(Cli::try_parse_from(["rn", "day", "2026-10-18", "--list"]).is_err());
+        assert!(Cli::try_parse_from(["rn", "day", "2026-10-18", "--from", "2026-10-01"]).is_err());
+    }
+
+    #[test]
+    fn test_move() {
+        assert!(Cli::try_parse_from(["rn", "mv", "a"]).is_err());
+        assert!(Cli::try_parse_from(["rn", "mv", "a", " "]).is_err());
+
+        let cli = Cli::parse_from(["rn", "move", "a", "b"]);
+        let Subcommand::Move(args) = cli.subcommand else {
+            panic!()
+        };
+        assert_eq!((args.name.as_str(), args.new_name.as_str()), ("a", "b"));
+        assert!(!args.force);
+        assert_eq!(args.to_notebook, None);
+
+        let cli = Cli::parse_from(["rn", "mv", "a", "b", "-f", "--to-notebook", "work"]);
+        let Subcommand::Move(args) = cli.subcommand else {
+            panic!()
+        };
+        assert!(args.force);
+        assert_eq!(args.to_notebook.unwrap(), "work");
+
+        let cli = Cli::parse_from(["rn", "archive", "mv", "a", "b"]);
+        let Subcommand::Archive(archive_args) = cli.subcommand else {
+            panic!()
+        };
+        assert!(matches!(
+            archive_args.subcommand,
+            ArchiveSubcommand::Move(_)
+        ));
+    }
+
+    #[test]
+    fn test_copy() {
+        assert!(Cli::try_parse_from(["rn", "cp", "a"]).is_err());
+
+        let cli = Cli::parse_from(["rn", "copy", "a", "b"]);
+        let Subcommand::Copy(args) = cli.subcommand else {
+            panic!()
+        };
+        assert_eq!(
+            (args.source.as_str(), args.destination.as_str()),
+            ("a", "b")
+        );
+        assert!(!args.archive);
+        assert!(!args.only_header);
+
+        let cli = Cli::parse_from(["rn", "cp", "-a", "a", "b", "--only-header"]);
+        let Subcommand::Copy(args) = cli.subcommand else {
+            panic!()
+        };
+        assert!(args.archive);
+        assert!(args.only_header);
+    }
+
+    #[test]
+    fn test_remove_permanent() {
+        let cli = Cli::parse_from(["rn", "rm", "a", "--permanent"]);
+        let Subcommand::Remove(args) = cli.subcommand else {
+            panic!()
+        };
+        assert!(args.permanent);
+        let cli = Cli::parse_from(["rn", "archive", "rm", "a", "--permanent"]);
+        let Subcommand::Archive(archive_args) = cli.subcommand else {
+            panic!()
+        };
+        let ArchiveSubcommand::Remove(remove_args) = archive_args.subcommand else {
+            panic!()
+        };
+        assert!(remove_args.permanent);
+    }
+
+    #[test]
+    fn test_trash() {
+        assert!(Cli::try_parse_from(["rn", "trash"]).is_err());
+        assert!(Cli::try_parse_from(["rn", "trash", "restore"]).is_err());
+        assert!(Cli::try_parse_from(["rn", "trash", "purge"]).is_err());
+
+        let cli = Cli::parse_from(["rn", "trash", "restore", "todo", "-n", "todo-2"]);
+        let Subcommand::Trash(trash_args) = cli.subcommand else {
+            panic!()
+        };
+        let TrashSubcommand::Restore(restore_args) = trash_args.subcommand else {
+            panic!()
+        };
+        assert_eq!(restore_args.entry, "todo");
+        assert_eq!(restore_args.new_name.unwrap(), "todo-2");
+
+        let cli = Cli::parse_from(["rn", "trash", "purge", "--older-than", "30d"]);
+        let Subcommand::Trash(trash_args) = cli.subcommand else {
+            panic!()
+        };
+        let TrashSubcommand::Purge(purge_args) = trash_args.subcommand else {
+            panic!()
+        };
+        assert_eq!(purge_args.older_than, Duration::from_hours(30 * 24));
+    }
+
+    #[test]
+    fn test_age() {
+        assert_eq!(age("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(age("2w"), Ok(Duration::from_hours(2 * 7 * 24)));
+        for invalid in ["", "d", "30", "-1d", "1.5h", "30x"] {
+            assert!(age(invalid).is_err());
+        }
+    }
+
+    #[test]
+    fn test_undo_redo_history() {
+        assert!(matches!(
+            Cli::parse_from(["rn", "undo"]).subcommand,
+            Subcommand::Undo
+        ));
+        assert!(matches!(
+            Cli::parse_from(["rn", "redo"]).subcommand,
+            Subcommand::Redo
+        ));
+        assert!(matches!(
+            Cli::parse_from(["rn", "history"]).subcommand,
+            Subcommand::History
+        ));
+        assert!(Cli::try_parse_from(["rn", "undo", "2"]).is_err());
     }
 
     #[test]
     fn test_list_additional_argument() {
-        assert!(Cli::try_parse_from(["rn", "list", "my_note"]).is_err());
+        assert!(Cli::try_parse_from(["rn", "list", "meeting-*", "my_note"]).is_err());
+        assert!(Cli::try_parse_from(["rn", "list", "--regex"]).is_err());
     }
 
     #[test]
     fn test_list() {
         let cli = Cli::parse_from(["rn", "list"]);
-        assert!(matches!(cli.subcommand, Subcommand::List));
+        let Subcommand::List(args) = cli.subcommand else {
+            panic!()
+        };
+        assert!(!args.flat);
+
+        let cli = Cli::parse_from(["rn", "ls", "--flat"]);
+        let Subcommand::List(args) = cli.subcommand else {
+            panic!()
+        };
+        assert!(args.flat);
     }
 
     #[test]
@@ -416,7 +1417,14 @@ mod tests {
 
     #[test]
     fn test_archive_open() {
-        assert!(Cli::try_parse_from(["rn", "archive", "open"]).is_err());
+        let cli = Cli::parse_from(["rn", "archive", "open"]);
+        let Subcommand::Archive(archive_args) = cli.subcommand else {
+            panic!();
+        };
+        let ArchiveSubcommand::Open(open_args) = archive_args.subcommand else {
+            panic!();
+        };
+        assert_eq!(open_args.name, None);
 
         assert!(Cli::try_parse_from(["rn", "archive", "open", "nb_1", "nb_2"]).is_err());
 
@@ -427,7 +1435,7 @@ mod tests {
         let ArchiveSubcommand::Open(open_args) = archive_args.subcommand else {
             panic!();
         };
-        assert_eq!(open_args.name, "nb_1");
+        assert_eq!(open_args.name.unwrap(), "nb_1");
 
         let cli = Cli::parse_from(["rn", "archive", "open", "-e", "nvim", "nb_1"]);
         let Subcommand::Archive(archive_args) = cli.subcommand else {
@@ -437,24 +1445,32 @@ mod tests {
             panic!();
         };
         assert_eq!(open_args.editor.unwrap(), "nvim");
-        assert_eq!(open_args.name, "nb_1");
+        assert_eq!(open_args.name.unwrap(), "nb_1");
     }
 
     #[test]
     fn test_archive_restore() {
         assert!(Cli::try_parse_from(["rn", "archive", "restore"]).is_err());
 
-        assert!(Cli::try_parse_from(["rn", "archive", "restore", "nb_1", "nb_2"]).is_err());
-
-        let cli = Cli::parse_from(["rn", "archive", "restore", "nb_1"]);
+        let cli = Cli::parse_from(["rn", "archive", "restore", "nb_1", "nb_2"]);
         let Subcommand::Archive(archive_args) = cli.subcommand else {
             panic!();
         };
         let ArchiveSubcommand::Restore(restore_args) = archive_args.subcommand else {
             panic!();
         };
-        assert_eq!(restore_args.archive_name, "nb_1");
+        assert_eq!(restore_args.archive_names, ["nb_1", "nb_2"]);
         assert_eq!(restore_args.new_name, None);
+        assert!(!restore_args.keep);
+
+        let cli = Cli::parse_from(["rn", "archive", "restore", "nb_1", "--keep"]);
+        let Subcommand::Archive(archive_args) = cli.subcommand else {
+            panic!();
+        };
+        let ArchiveSubcommand::Restore(restore_args) = archive_args.subcommand else {
+            panic!();
+        };
+        assert!(restore_args.keep);
 
         let cli = Cli::parse_from(["rn", "archive", "restore", "nb_1", "--new-name", "nb"]);
         let Subcommand::Archive(archive_args) = cli.subcommand else {
@@ -463,10 +1479,27 @@ mod tests {
         let ArchiveSubcommand::Restore(restore_args) = archive_args.subcommand else {
             panic!();
         };
-        assert_eq!(restore_args.archive_name, "nb_1");
+        assert_eq!(restore_args.archive_names, ["nb_1"]);
         assert_eq!(restore_args.new_name, Some(String::from("nb")));
     }
 
+    #[test]
+    fn test_archive_diff() {
+        let cli = Cli::parse_from(["rn", "archive", "diff", "todo", "1"]);
+        let Subcommand::Archive(archive_args) = cli.subcommand else {
+            panic!();
+        };
+        let ArchiveSubcommand::Diff(diff_args) = archive_args.subcommand else {
+            panic!();
+        };
+        assert_eq!(diff_args.name, "todo");
+        assert_eq!(diff_args.from, Some(1));
+        assert_eq!(diff_args.to, None);
+
+        assert!(Cli::try_parse_from(["rn", "archive", "diff", "todo", "first"]).is_err());
+        assert!(Cli::try_parse_from(["rn", "archive", "history"]).is_err());
+    }
+
     #[test]
     fn test_archive_remove() {
         assert!(Cli::try_parse_from(["rn", "archive", "remove"]).is_err());
@@ -482,4 +1515,175 @@ mod tests {
         };
         assert_eq!(remove_args.name, "nb_1");
     }
+
+    #[test]
+    fn test_notebook_flag() {
+        let cli = Cli::parse_from(["rn", "list"]);
+        assert_eq!(cli.notebook, None);
+
+        let cli = Cli::parse_from(["rn", "--notebook", "work", "list"]);
+        assert_eq!(cli.notebook, Some(String::from("work")));
+
+        let cli = Cli::parse_from(["rn", "archive", "list", "--notebook", "work"]);
+        assert_eq!(cli.notebook, Some(String::from("work")));
+    }
+
+    #[test]
+    fn test_exact_flag() {
+        let cli = Cli::parse_from(["rn", "open", "my_note"]);
+        assert!(!cli.exact);
+
+        let cli = Cli::parse_from(["rn", "--exact", "open", "my_note"]);
+        assert!(cli.exact);
+
+        let cli = Cli::parse_from(["rn", "archive", "rm", "my_note", "--exact"]);
+        assert!(cli.exact);
+    }
+
+    #[test]
+    fn test_notebook_no_subcommand() {
+        assert!(Cli::try_parse_from(["rn", "notebook"]).is_err());
+        assert!(Cli::try_parse_from(["rn", "notebook", "test"]).is_err());
+    }
+
+    #[test]
+    fn test_notebook_create() {
+        assert!(Cli::try_parse_from(["rn", "notebook", "create"]).is_err());
+        assert!(Cli::try_parse_from(["rn", "notebook", "create", " "]).is_err());
+
+        let cli = Cli::parse_from(["rn", "notebook", "create", "work"]);
+        let Subcommand::Notebook(notebook_args) = cli.subcommand else {
+            panic!();
+        };
+        let NotebookSubcommand::Create(create_args) = notebook_args.subcommand else {
+            panic!();
+        };
+        assert_eq!(create_args.name, "work");
+    }
+
+    #[test]
+    fn test_notebook_list() {
+        assert!(Cli::try_parse_from(["rn", "notebook", "list", "test"]).is_err());
+
+        let cli = Cli::parse_from(["rn", "notebook", "ls"]);
+        let Subcommand::Notebook(notebook_args) = cli.subcommand else {
+            panic!();
+        };
+        assert!(matches!(notebook_args.subcommand, NotebookSubcommand::List));
+    }
+
+    #[test]
+    fn test_notebook_remove() {
+        assert!(Cli::try_parse_from(["rn", "notebook", "remove"]).is_err());
+
+        let cli = Cli::parse_from(["rn", "notebook", "rm", "work"]);
+        let Subcommand::Notebook(notebook_args) = cli.subcommand else {
+            panic!();
+        };
+        let NotebookSubcommand::Remove(remove_args) = notebook_args.subcommand else {
+            panic!();
+        };
+        assert_eq!(remove_args.name, "work");
+        assert!(!remove_args.force);
+
+        let cli = Cli::parse_from(["rn", "notebook", "remove", "work", "--force"]);
+        let Subcommand::Notebook(notebook_args) = cli.subcommand else {
+            panic!();
+        };
+        let NotebookSubcommand::Remove(remove_args) = notebook_args.subcommand else {
+            panic!();
+        };
+        assert!(remove_args.force);
+    }
+
+    #[test]
+    fn test_notebook_rename() {
+        assert!(Cli::try_parse_from(["rn", "notebook", "rename", "work"]).is_err());
+
+        let cli = Cli::parse_from(["rn", "notebook", "rename", "work", "job"]);
+        let Subcommand::Notebook(notebook_args) = cli.subcommand else {
+            panic!();
+        };
+        let NotebookSubcommand::Rename(rename_args) = notebook_args.subcommand else {
+            panic!();
+        };
+        assert_eq!(rename_args.name, "work");
+        assert_eq!(rename_args.new_name, "job");
+    }
+
+    #[test]
+    fn test_notebook_use() {
+        assert!(Cli::try_parse_from(["rn", "notebook", "use"]).is_err());
+        assert!(Cli::try_parse_from(["rn", "notebook", "use", "a", "b"]).is_err());
+
+        let cli = Cli::parse_from(["rn", "notebook", "use", "work"]);
+        let Subcommand::Notebook(notebook_args) = cli.subcommand else {
+            panic!();
+        };
+        let NotebookSubcommand::Use(use_args) = notebook_args.subcommand else {
+            panic!();
+        };
+        assert_eq!(use_args.name, "work");
+    }
+
+    #[test]
+    fn test_search() {
+        assert!(Cli::try_parse_from(["rn", "search"]).is_err());
+        assert!(Cli::try_parse_from(["rn", "search", "a", "b"]).is_err());
+
+        let cli = Cli::parse_from(["rn", "search", "todo"]);
+        let Subcommand::Search(args) = cli.subcommand else {
+            panic!()
+        };
+        assert_eq!(args.query, "todo");
+        assert!(!args.ignore_case);
+        assert!(!args.word);
+        assert!(!args.regex);
+        assert!(!args.archive);
+
+        let cli = Cli::parse_from(["rn", "search", "-i", "-w", "-r", "-a", "to.o"]);
+        let Subcommand::Search(args) = cli.subcommand else {
+            panic!()
+        };
+        assert_eq!(args.query, "to.o");
+        assert!(args.ignore_case);
+        assert!(args.word);
+        assert!(args.regex);
+        assert!(args.archive);
+
+        let cli = Cli::parse_from([
+            "rn",
+            "search",
+            "--ignore-case",
+            "--word",
+            "--regex",
+            "--archive",
+            "todo",
+        ]);
+        let Subcommand::Search(args) = cli.subcommand else {
+            panic!()
+        };
+        assert!(args.ignore_case && args.word && args.regex && args.archive);
+        assert!(!args.ranked);
+
+        let cli = Cli::parse_from(["rn", "search", "--ranked", "todo"]);
+        let Subcommand::Search(args) = cli.subcommand else {
+            panic!()
+        };
+        assert!(args.ranked);
+
+        assert!(Cli::try_parse_from(["rn", "search", "--ranked", "--regex", "todo"]).is_err());
+    }
+
+    #[test]
+    fn test_index() {
+        assert!(Cli::try_parse_from(["rn", "index"]).is_err());
+        assert!(Cli::try_parse_from(["rn", "index", "rebuild", "test"]).is_err());
+
+        let cli = Cli::parse_from(["rn", "index", "rebuild"]);
+        let Subcommand::Index(index_args) = cli.subcommand else {
+            panic!()
+        };
+        assert!(matches!(index_args.subcommand, IndexSubcommand::Rebuild));
+    }
 }
@@ -1,3 +1,4 @@
+use std::fmt::Display;
 use std::path::PathBuf;
 
 use anyhow::Result;
@@ -10,8 +11,34 @@ use crate::{
 
 pub mod value_names {
     pub const EDITOR: &str = "editor";
+    pub const ROOT_DIR: &str = "root_dir";
+    pub const PAGER: &str = "pager";
+    pub const DEFAULT_TEMPLATE: &str = "default_template";
+    pub const JOURNAL_DATE_FORMAT: &str = "journal_date_format";
+    pub const JOURNAL_TEMPLATE: &str = "journal_template";
+    pub const SNAPSHOT_ON_OPEN: &str = "snapshot_on_open";
+    pub const ARCHIVE_KEEP_LAST: &str = "archive_keep_last";
+    pub const ARCHIVE_KEEP_DAILY: &str = "archive_keep_daily";
+    pub const ARCHIVE_KEEP_WEEKLY: &str = "archive_keep_weekly";
+    pub const ARCHIVE_KEEP_MONTHLY: &str = "archive_keep_monthly";
+    pub const ARCHIVE_MAX_AGE: &str = "archive_max_age";
+    pub const ARCHIVE_FORMAT: &str = "archive_format";
 
-    pub const ALL: [&str; 1] = [EDITOR];
+    pub const ALL: [&str; 13] = [
+        EDITOR,
+        ROOT_DIR,
+        PAGER,
+        DEFAULT_TEMPLATE,
+        JOURNAL_DATE_FORMAT,
+        JOURNAL_TEMPLATE,
+        SNAPSHOT_ON_OPEN,
+        ARCHIVE_KEEP_LAST,
+        ARCHIVE_KEEP_DAILY,
+        ARCHIVE_KEEP_WEEKLY,
+        ARCHIVE_KEEP_MONTHLY,
+        ARCHIVE_MAX_AGE,
+        ARCHIVE_FORMAT,
+    ];
 }
 
 fn config_dir() -> Result<PathBuf> {
@@ -27,15 +54,65 @@ pub fn config_file() -> Result<PathBuf> {
     Ok(path)
 }
 
+/// How notes are stored in the archive.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveFormat {
+    #[default]
+    Plain,
+    Gzip,
+}
+
+impl Display for ArchiveFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Plain => write!(f, "plain"),
+            Self::Gzip => write!(f, "gzip"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct Config {
     pub editor: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root_dir: Option<PathBuf>,
+    pub pager: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_template: Option<String>,
+    pub journal_date_format: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub journal_template: Option<String>,
+    pub snapshot_on_open: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive_keep_last: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive_keep_daily: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive_keep_weekly: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive_keep_monthly: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive_max_age: Option<String>,
+    pub archive_format: ArchiveFormat,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             editor: String::from("nvim"),
+            root_dir: None,
+            pager: String::from("less -FR"),
+            default_template: None,
+            journal_date_format: String::from("%Y-%m-%d"),
+            journal_template: None,
+            snapshot_on_open: false,
+            archive_keep_last: None,
+            archive_keep_daily: None,
+            archive_keep_weekly: None,
+            archive_keep_monthly: None,
+            archive_max_age: None,
+            archive_format: ArchiveFormat::Plain,
         }
     }
 }
@@ -51,6 +128,42 @@ impl Config {
         if let Some(editor) = partial_config.editor {
             self.editor = editor;
         }
+        if let Some(root_dir) = partial_config.root_dir {
+            self.root_dir = Some(root_dir);
+        }
+        if let Some(pager) = partial_config.pager {
+            self.pager = pager;
+        }
+        if let Some(default_template) = partial_config.default_template {
+            self.default_template = Some(default_template);
+        }
+        if let Some(journal_date_format) = partial_config.journal_date_format {
+            self.journal_date_format = journal_date_format;
+        }
+        if let Some(journal_template) = partial_config.journal_template {
+            self.journal_template = Some(journal_template);
+        }
+        if let Some(snapshot_on_open) = partial_config.snapshot_on_open {
+            self.snapshot_on_open = snapshot_on_open;
+        }
+        if let Some(archive_keep_last) = partial_config.archive_keep_last {
+            self.archive_keep_last = Some(archive_keep_last);
+        }
+        if let Some(archive_keep_daily) = partial_config.archive_keep_daily {
+            self.archive_keep_daily = Some(archive_keep_daily);
+        }
+        if let Some(archive_keep_weekly) = partial_config.archive_keep_weekly {
+            self.archive_keep_weekly = Some(archive_keep_weekly);
+        }
+        if let Some(archive_keep_monthly) = partial_config.archive_keep_monthly {
+            self.archive_keep_monthly = Some(archive_keep_monthly);
+        }
+        if let Some(archive_max_age) = partial_config.archive_max_age {
+            self.archive_max_age = Some(archive_max_age);
+        }
+        if let Some(archive_format) = partial_config.archive_format {
+            self.archive_format = archive_format;
+        }
     }
 
     pub fn to_toml(&self) -> Result<String> {
@@ -61,6 +174,18 @@ impl Config {
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct PartialConfig {
     pub editor: Option<String>,
+    pub root_dir: Option<PathBuf>,
+    pub pager: Option<String>,
+    pub default_template: Option<String>,
+    pub journal_date_format: Option<String>,
+    pub journal_template: Option<String>,
+    pub snapshot_on_open: Option<bool>,
+    pub archive_keep_last: Option<usize>,
+    pub archive_keep_daily: Option<usize>,
+    pub archive_keep_weekly: Option<usize>,
+    pub archive_keep_monthly: Option<usize>,
+    pub archive_max_age: Option<String>,
+    pub archive_format: Option<ArchiveFormat>,
 }
 
 impl PartialConfig {
@@ -1,25 +1,102 @@
 use std::{ffi::OsString, fmt::Display, path::PathBuf};
 
 use anyhow::Error;
+use serde::Serialize;
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+/// Serialized with the variant name as `kind` and its values as `details`.
+#[derive(Error, Debug, Serialize)]
+#[serde(tag = "kind", content = "details", rename_all = "snake_case")]
 pub enum AppError {
     AlreadyExists(String),
     NotFound(String),
+    Ambiguous(String, Vec<String>),
+    InvalidNoteName(String),
     ConfigAlreadyExists(PathBuf),
     RestoreAlreadyExists(String),
+    NewNameForMultipleNotes,
+    VersionNotFound(String, usize),
+    SingleVersion(String),
+    FieldNotFound(String, String),
+    InvalidFieldName(String),
+    TagNotFound(String, String),
+    InvalidTag(String),
+    InvalidConfigValue(String, String),
     ArchiveAlreadyExists(String),
+    NotebookNotFound(String),
+    NotebookAlreadyExists(String),
+    NotebookNotEmpty(String),
+    NotebookInTrash(String),
+    InvalidNotebookName(String),
+    DefaultNotebook,
+    NoMatches(String),
+    InvalidSearchPattern(String),
+    TemplateNotFound(String),
+    TemplateAlreadyExists(String),
+    UnknownTemplateVariable(String),
+    /// The journal date format if it's accepted besides `YYYY-MM-DD`.
+    InvalidDate(String, Option<String>),
+    InvalidDateFormat(String),
+    NotInTrash(String),
+    NothingToUndo,
+    NothingToRedo,
+    ChangedSinceOperation(PathBuf),
 }
 impl Display for AppError {
+    #[allow(clippy::too_many_lines)] // one arm per error
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::AlreadyExists(name) => writeln!(f, "A note named \"{name}\" already exists."),
             Self::NotFound(name) => writeln!(f, "No note named \"{name}\" exists."),
+            Self::Ambiguous(name, suggestions) => {
+                writeln!(f, "\"{name}\" matches multiple notes, did you mean:")?;
+                for suggestion in suggestions {
+                    writeln!(f, "  {suggestion}")?;
+                }
+                Ok(())
+            }
+            Self::InvalidNoteName(name) => {
+                writeln!(f, "\"{name}\" is not a valid note name.")?;
+                writeln!(
+                    f,
+                    "Folders are separated by \"/\", their names must not be empty or start with \".\"."
+                )
+            }
             Self::ConfigAlreadyExists(path) => {
                 writeln!(f, "A config file already exists {}.", path.display())?;
                 writeln!(f, "To overwrite it with the default use `--force`.")
             }
+            Self::InvalidConfigValue(name, reason) => {
+                writeln!(f, "Invalid value for \"{name}\" in the config: {reason}")
+            }
+            Self::VersionNotFound(name, version) => {
+                writeln!(f, "The archive has no version {version} of \"{name}\".")?;
+                writeln!(
+                    f,
+                    "Use `rn archive history {name}` to list the archived versions."
+                )
+            }
+            Self::SingleVersion(name) => {
+                writeln!(f, "Only one version of \"{name}\" is archived, nothing to compare.")?;
+                writeln!(f, "Archive the note again with `rn archive save {name}`.")
+            }
+            Self::FieldNotFound(name, key) => {
+                writeln!(f, "The front matter of note \"{name}\" has no field \"{key}\".")
+            }
+            Self::InvalidFieldName(key) => writeln!(
+                f,
+                "\"{key}\" is not a valid field name, use letters, digits, \"_\" and \"-\"."
+            ),
+            Self::TagNotFound(name, tag) => {
+                writeln!(f, "The note \"{name}\" isn't tagged \"{tag}\" in its front matter.")
+            }
+            Self::InvalidTag(tag) => writeln!(
+                f,
+                "\"{tag}\" is not a valid tag, use letters, digits, \"_\", \"-\" and \"/\"."
+            ),
+            Self::NewNameForMultipleNotes => {
+                writeln!(f, "A new name can only be given when restoring a single note.")
+            }
             Self::RestoreAlreadyExists(name) => {
                 writeln!(
                     f,
@@ -35,6 +112,70 @@ impl Display for AppError {
                 f,
                 "Archiving failed, because file \"{name}\" already exists."
             ),
+            Self::NotebookNotFound(name) => {
+                writeln!(f, "No notebook named \"{name}\" exists.")?;
+                writeln!(f, "Use `rn notebook ls` to list the existing notebooks.")
+            }
+            Self::NotebookAlreadyExists(name) => {
+                writeln!(f, "A notebook named \"{name}\" already exists.")
+            }
+            Self::NotebookInTrash(name) => {
+                writeln!(f, "The trash still contains notes of the notebook \"{name}\".")?;
+                writeln!(f, "Restore them or use `rn trash empty` before renaming it.")
+            }
+            Self::NotebookNotEmpty(name) => {
+                writeln!(f, "The notebook \"{name}\" still contains notes.")?;
+                writeln!(f, "To delete it anyway use `--force`.")
+            }
+            Self::InvalidNotebookName(name) => writeln!(
+                f,
+                "\"{name}\" is not a valid notebook name, it must not be empty, contain \"/\" or start with \".\"."
+            ),
+            Self::DefaultNotebook => {
+                writeln!(f, "The default notebook can't be removed or renamed.")
+            }
+            Self::NoMatches(query) => writeln!(f, "No note matches \"{query}\"."),
+            Self::InvalidSearchPattern(error) => {
+                writeln!(f, "Invalid search pattern:")?;
+                writeln!(f, "{error}")
+            }
+            Self::TemplateNotFound(name) => {
+                writeln!(f, "No template named \"{name}\" exists.")?;
+                writeln!(f, "Use `rn template ls` to list the existing templates.")
+            }
+            Self::TemplateAlreadyExists(name) => {
+                writeln!(f, "A template named \"{name}\" already exists.")
+            }
+            Self::UnknownTemplateVariable(name) => {
+                writeln!(f, "The template uses the unknown variable \"{name}\".")?;
+                writeln!(f, "Pass its value with `--var {name}=VALUE`.")
+            }
+            Self::InvalidDate(date, format) => {
+                writeln!(f, "\"{date}\" is not a valid date.")?;
+                match format {
+                    Some(format) => {
+                        writeln!(f, "Use YYYY-MM-DD or the journal date format \"{format}\".")
+                    }
+                    None => writeln!(f, "Use YYYY-MM-DD."),
+                }
+            }
+            Self::NotInTrash(query) => {
+                writeln!(f, "No note \"{query}\" is in the trash.")?;
+                writeln!(f, "Use `rn trash ls` to list the deleted notes.")
+            }
+            Self::NothingToUndo => writeln!(f, "There is no operation to undo."),
+            Self::NothingToRedo => writeln!(f, "There is no undone operation to redo."),
+            Self::ChangedSinceOperation(path) => {
+                writeln!(
+                    f,
+                    "\"{}\" was changed since the operation, so it's left as it is.",
+                    path.display()
+                )
+            }
+            Self::InvalidDateFormat(format) => writeln!(
+                f,
+                "\"{format}\" is not a valid date format, check the `journal_date_format` config value."
+            ),
         }
     }
 }
@@ -43,6 +184,7 @@ impl Display for AppError {
 pub enum SystemError {
     CommandNotInstalled(String),
     NoHomeDir,
+    NoTerminal,
 }
 
 impl Display for SystemError {
@@ -52,6 +194,12 @@ impl Display for SystemError {
                 writeln!(f, "The command \"{command}\" is not installed.")
             }
             Self::NoHomeDir => writeln!(f, "No home directory could be found."),
+            Self::NoTerminal => {
+                writeln!(
+                    f,
+                    "No note name was given and there is no terminal to pick one."
+                )
+            }
         }
     }
 }
@@ -86,3 +234,59 @@ impl Display for InternalError {
         )
     }
 }
+
+/// Exit code of the error class, clap uses 2 for usage errors.
+pub fn exit_code(error: &Error) -> u8 {
+    if let Some(app_error) = error.downcast_ref::<AppError>() {
+        return app_error.exit_code();
+    }
+    if error.is::<SystemError>() {
+        5
+    } else if error.is::<FileSystemError>() || error.is::<std::io::Error>() {
+        6
+    } else if error.is::<InternalError>() {
+        7
+    } else {
+        1
+    }
+}
+
+impl AppError {
+    fn exit_code(&self) -> u8 {
+        match self {
+            Self::InvalidNoteName(_)
+            | Self::InvalidNotebookName(_)
+            | Self::InvalidSearchPattern(_)
+            | Self::InvalidDate(..)
+            | Self::NewNameForMultipleNotes
+            | Self::InvalidFieldName(_)
+            | Self::InvalidTag(_)
+            | Self::InvalidConfigValue(..)
+            | Self::InvalidDateFormat(_)
+            | Self::UnknownTemplateVariable(_) => 2,
+            // an ambiguous name doesn't identify a note either
+            Self::NotFound(_)
+            | Self::Ambiguous(..)
+            | Self::NotebookNotFound(_)
+            | Self::TemplateNotFound(_)
+            | Self::VersionNotFound(..)
+            | Self::SingleVersion(_)
+            | Self::FieldNotFound(..)
+            | Self::TagNotFound(..)
+            | Self::NotInTrash(_)
+            | Self::NoMatches(_) => 3,
+            Self::AlreadyExists(_)
+            | Self::ConfigAlreadyExists(_)
+            | Self::RestoreAlreadyExists(_)
+            | Self::ArchiveAlreadyExists(_)
+            | Self::NotebookAlreadyExists(_)
+            | Self::TemplateAlreadyExists(_) => 4,
+            Self::NotebookNotEmpty(_)
+            | Self::NotebookInTrash(_)
+            | Self::DefaultNotebook
+            | Self::NothingToUndo
+            | Self::NothingToRedo
+            | Self::ChangedSinceOperation(_) => 1,
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use anyhow::Result;
+use chrono::{DateTime, Local};
+use std::io::{ErrorKind, Write};
 use std::process::Stdio;
 use std::{
-    fs::{self, File},
+    fs::{self, File, OpenOptions},
     path::Path,
     process::Command,
 };
@@ -9,7 +11,7 @@ use std::{
 use crate::error::FileSystemError;
 use crate::error::SystemError;
 
-fn check_command(command_name: &str) -> Result<()> {
+pub fn check_command(command_name: &str) -> Result<()> {
     let check_command = "which";
     let status = std::process::Command::new(check_command)
         .arg(command_name)
@@ -25,16 +27,38 @@ fn check_command(command_name: &str) -> Result<()> {
     Ok(())
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileMetadata {
+    pub size: u64,
+    /// Falls back to the modification time where the creation time isn't
+    /// available.
+    pub created: DateTime<Local>,
+    pub modified: DateTime<Local>,
+}
+
 pub trait FileOperations {
     fn get_files(&self, dir: &Path) -> Result<Vec<String>>;
+    /// Lists all files below `dir` as `/` separated paths relative to `dir`.
+    fn get_files_recursive(&self, dir: &Path) -> Result<Vec<String>>;
     fn delete_file(&mut self, path: &Path) -> Result<()>;
     fn create_file(&mut self, path: &Path) -> Result<()>;
     fn create_dir(&mut self, path: &Path) -> Result<()>;
     fn open_file(&mut self, editor_command: &str, path: &Path) -> Result<()>;
     fn exists(&self, path: &Path) -> Result<bool>;
     fn read_file(&self, path: &Path) -> Result<String>;
+    fn read_bytes(&self, path: &Path) -> Result<Vec<u8>>;
     fn write_file(&mut self, path: &Path, value: &str) -> Result<()>;
+    fn write_bytes(&mut self, path: &Path, value: &[u8]) -> Result<()>;
+    /// Appends `value` to the file, creating it if it doesn't exist. Concurrent
+    /// appends don't interleave.
+    fn append_file(&mut self, path: &Path, value: &str) -> Result<()>;
     fn copy(&mut self, source_path: &Path, destination_path: &Path) -> Result<()>;
+    fn rename(&mut self, source_path: &Path, destination_path: &Path) -> Result<()>;
+    /// Deletes the directory at `path` if it's empty, returns whether it was
+    /// deleted.
+    fn delete_dir(&mut self, path: &Path) -> Result<bool>;
+    fn delete_dir_all(&mut self, path: &Path) -> Result<()>;
+    fn metadata(&self, path: &Path) -> Result<FileMetadata>;
 }
 
 pub struct FileSystem;
@@ -55,6 +79,21 @@ impl FileOperations for FileSystem {
         Ok(files)
     }
 
+    fn get_files_recursive(&self, dir: &Path) -> Result<Vec<String>> {
+        let mut files = Vec::new();
+        for name in self.get_files(dir)? {
+            let path = dir.join(&name);
+            if path.is_dir() {
+                for nested_name in self.get_files_recursive(&path)? {
+                    files.push(format!("{name}/{nested_name}"));
+                }
+            } else {
+                files.push(name);
+            }
+        }
+        Ok(files)
+    }
+
     fn delete_file(&mut self, path: &Path) -> Result<()> {
         fs::remove_file(path).map_err(Into::into)
     }
@@ -87,13 +126,56 @@ impl FileOperations for FileSystem {
         fs::read_to_string(path).map_err(Into::into)
     }
 
+    fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        fs::read(path).map_err(Into::into)
+    }
+
     fn write_file(&mut self, file_path: &Path, content: &str) -> Result<()> {
         fs::write(file_path, content).map_err(Into::into)
     }
 
+    fn write_bytes(&mut self, path: &Path, value: &[u8]) -> Result<()> {
+        fs::write(path, value).map_err(Into::into)
+    }
+
+    fn append_file(&mut self, path: &Path, value: &str) -> Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        // the lock keeps other rn processes out, a single write with
+        // O_APPEND also protects against writers which don't lock
+        file.lock()?;
+        file.write_all(value.as_bytes())?;
+        file.unlock().map_err(Into::into)
+    }
+
     fn copy(&mut self, source_path: &Path, destination_path: &Path) -> Result<()> {
         fs::copy(source_path, destination_path)
             .map(|_| ())
             .map_err(Into::into)
     }
+
+    fn rename(&mut self, source_path: &Path, destination_path: &Path) -> Result<()> {
+        fs::rename(source_path, destination_path).map_err(Into::into)
+    }
+
+    fn delete_dir(&mut self, path: &Path) -> Result<bool> {
+        match fs::remove_dir(path) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::DirectoryNotEmpty => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn delete_dir_all(&mut self, path: &Path) -> Result<()> {
+        fs::remove_dir_all(path).map_err(Into::into)
+    }
+
+    fn metadata(&self, path: &Path) -> Result<FileMetadata> {
+        let metadata = fs::metadata(path)?;
+        let modified = metadata.modified()?;
+        Ok(FileMetadata {
+            size: metadata.len(),
+            created: metadata.created().unwrap_or(modified).into(),
+            modified: modified.into(),
+        })
+    }
 }
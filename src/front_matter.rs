@@ -0,0 +1,469 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+
+/// Lines opening and closing YAML and TOML front matter.
+const DELIMITERS: [&str; 2] = ["---", "+++"];
+
+/// Fields whose values are always lists.
+pub const LIST_FIELDS: [&str; 2] = ["tags", "aliases"];
+
+/// Field set when a note is created.
+pub const CREATED: &str = "created";
+
+/// Field set when a note is created or changed in the editor.
+pub const UPDATED: &str = "updated";
+
+/// Splits `content` into its front matter, including the delimiter lines, and
+/// the rest. Returns `None` if the note doesn't start with front matter.
+pub fn split(content: &str) -> Option<(&str, &str)> {
+    let first_line_end = content.find('\n')?;
+    let delimiter = content.get(..first_line_end)?.trim_end_matches('\r');
+    if !DELIMITERS.contains(&delimiter) {
+        return None;
+    }
+    let mut offset = first_line_end + 1;
+    for line in content.get(offset..)?.split_inclusive('\n') {
+        offset += line.len();
+        if line.trim_end_matches(['\r', '\n']) == delimiter {
+            return Some((content.get(..offset)?, content.get(offset..)?));
+        }
+    }
+    None
+}
+
+/// Value of a front matter field, serialized as a string or a list.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum FieldValue {
+    Text(String),
+    List(Vec<String>),
+}
+
+impl FieldValue {
+    /// A list for list fields or multiple values, text otherwise.
+    pub fn new(key: &str, mut values: Vec<String>) -> Self {
+        if values.len() == 1 && !LIST_FIELDS.contains(&key) {
+            Self::Text(values.remove(0))
+        } else {
+            Self::List(values)
+        }
+    }
+}
+
+impl Display for FieldValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Text(text) => writeln!(f, "{text}"),
+            Self::List(items) => items.iter().try_for_each(|item| writeln!(f, "{item}")),
+        }
+    }
+}
+
+/// Keys are limited to what's valid in both YAML and TOML without quoting.
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Yaml,
+    Toml,
+}
+
+/// Front matter split into its top level fields. Every field keeps its
+/// lines, so fields which aren't changed keep their formatting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrontMatter {
+    format: Format,
+    /// Lines before the first field.
+    head: String,
+    /// Keys with the lines of their field.
+    fields: Vec<(String, String)>,
+    /// TOML tables, their fields aren't top level fields.
+    tail: String,
+}
+
+impl FrontMatter {
+    /// Parses the front matter of `content` and returns it together with the
+    /// rest of the note.
+    pub fn parse(content: &str) -> Option<(Self, &str)> {
+        let (header, body) = split(content)?;
+        let (delimiter, inner) = header.split_once('\n')?;
+        let inner = inner
+            .trim_end_matches(['\r', '\n'])
+            .strip_suffix(delimiter.trim_end_matches('\r'))?;
+        let format = if delimiter.starts_with('+') {
+            Format::Toml
+        } else {
+            Format::Yaml
+        };
+        let mut front_matter = Self {
+            format,
+            head: String::new(),
+            fields: Vec::new(),
+            tail: String::new(),
+        };
+        for line in inner.split_inclusive('\n') {
+            if !front_matter.tail.is_empty() || (format == Format::Toml && line.starts_with('[')) {
+                front_matter.tail.push_str(line);
+            } else if let Some(key) = field_key(line, format) {
+                front_matter.fields.push((key.to_owned(), line.to_owned()));
+            } else if let Some((_, lines)) = front_matter.fields.last_mut() {
+                lines.push_str(line);
+            } else {
+                front_matter.head.push_str(line);
+            }
+        }
+        Some((front_matter, body))
+    }
+
+    /// Empty YAML front matter.
+    pub fn new() -> Self {
+        Self {
+            format: Format::Yaml,
+            head: String::new(),
+            fields: Vec::new(),
+            tail: String::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.head.trim().is_empty() && self.tail.trim().is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<FieldValue> {
+        let (_, lines) = self.fields.iter().find(|(field, _)| field == key)?;
+        Some(match self.format {
+            Format::Yaml => yaml_value(lines),
+            Format::Toml => toml_value(key, lines)?,
+        })
+    }
+
+    /// Replaces the value of `key`, new fields are added at the end.
+    pub fn set(&mut self, key: &str, value: &FieldValue) {
+        let lines = match self.format {
+            Format::Yaml => format!("{key}: {}\n", yaml_text(value)),
+            Format::Toml => format!("{key} = {}\n", toml_text(value)),
+        };
+        match self.fields.iter_mut().find(|(field, _)| field == key) {
+            Some((_, field_lines)) => *field_lines = lines,
+            None => self.fields.push((key.to_owned(), lines)),
+        }
+    }
+
+    /// Removes `key` and returns whether it was set.
+    pub fn unset(&mut self, key: &str) -> bool {
+        let count = self.fields.len();
+        self.fields.retain(|(field, _)| field != key);
+        self.fields.len() != count
+    }
+}
+
+impl Display for FrontMatter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let delimiter = match self.format {
+            Format::Yaml => DELIMITERS[0],
+            Format::Toml => DELIMITERS[1],
+        };
+        writeln!(f, "{delimiter}")?;
+        write!(f, "{}", self.head)?;
+        for (_, lines) in &self.fields {
+            write!(f, "{lines}")?;
+        }
+        write!(f, "{}", self.tail)?;
+        writeln!(f, "{delimiter}")
+    }
+}
+
+/// Sets the `created` and `updated` fields to `time`, `created` only if
+/// `is_new` is set and it isn't set yet. Returns `None` for notes without
+/// front matter, they're left as they are.
+pub fn touch(content: &str, time: &str, is_new: bool) -> Option<String> {
+    let (mut front_matter, body) = FrontMatter::parse(content)?;
+    let time = FieldValue::Text(time.to_owned());
+    if is_new && front_matter.get(CREATED).is_none() {
+        front_matter.set(CREATED, &time);
+    }
+    front_matter.set(UPDATED, &time);
+    Some(format!("{front_matter}{body}"))
+}
+
+/// Key of the field starting in `line`, `None` for comments, blank lines and
+/// lines continuing the previous field.
+fn field_key(line: &str, format: Format) -> Option<&str> {
+    if line.starts_with([' ', '\t', '#', '-', '\r', '\n']) {
+        return None;
+    }
+    let key = match format {
+        Format::Yaml => {
+            let (key, rest) = line.split_once(':')?;
+            if !rest.is_empty() && !rest.starts_with([' ', '\t', '\r', '\n']) {
+                return None;
+            }
+            key
+        }
+        Format::Toml => line.split_once('=')?.0,
+    };
+    Some(key.trim().trim_matches(['"', '\'']))
+}
+
+/// Value of YAML field `lines`, block and flow lists are lists, other values
+/// are text.
+fn yaml_value(lines: &str) -> FieldValue {
+    let (first, rest) = lines.split_once('\n').unwrap_or((lines, ""));
+    let value = first.split_once(':').map_or("", |(_, value)| value).trim();
+    if value.is_empty() {
+        let lines: Vec<&str> = rest
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+        let items: Option<Vec<String>> = lines
+            .iter()
+            .map(|line| Some(yaml_scalar(line.strip_prefix('-')?.trim())))
+            .collect();
+        return match items {
+            Some(items) if !items.is_empty() => FieldValue::List(items),
+            _ => FieldValue::Text(lines.join("\n")),
+        };
+    }
+    match value
+        .strip_prefix('[')
+        .and_then(|value| value.strip_suffix(']'))
+    {
+        Some(items) => FieldValue::List(
+            split_flow_list(items)
+                .into_iter()
+                .map(|item| yaml_scalar(item.trim()))
+                .collect(),
+        ),
+        None => FieldValue::Text(yaml_scalar(value)),
+    }
+}
+
+/// Splits the items of a flow list at commas outside of quotes.
+fn split_flow_list(items: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    for (index, c) in items.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, ',') => {
+                parts.push(items.get(start..index).unwrap_or_default());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(items.get(start..).unwrap_or_default());
+    parts.retain(|part| !part.trim().is_empty());
+    parts
+}
+
+fn yaml_scalar(value: &str) -> String {
+    if let Some(text) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    {
+        let mut unescaped = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            let c = if c == '\\' {
+                match chars.next() {
+                    Some('n') => '\n',
+                    Some('r') => '\r',
+                    Some('t') => '\t',
+                    Some(escaped) => escaped,
+                    None => c,
+                }
+            } else {
+                c
+            };
+            unescaped.push(c);
+        }
+        return unescaped;
+    }
+    if let Some(text) = value
+        .strip_prefix('\'')
+        .and_then(|value| value.strip_suffix('\''))
+    {
+        return text.replace("''", "'");
+    }
+    value
+        .split_once(" #")
+        .map_or(value, |(value, _)| value)
+        .trim_end()
+        .to_owned()
+}
+
+fn yaml_text(value: &FieldValue) -> String {
+    match value {
+        FieldValue::Text(text) => yaml_quote(text, false),
+        FieldValue::List(items) => {
+            let items: Vec<String> = items.iter().map(|item| yaml_quote(item, true)).collect();
+            format!("[{}]", items.join(", "))
+        }
+    }
+}
+
+/// Quotes text which YAML wouldn't read back as the same string.
+fn yaml_quote(text: &str, in_list: bool) -> String {
+    let needs_quotes = text.is_empty()
+        || text.trim() != text
+        || text.starts_with([
+            '-', '?', ':', ',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%',
+            '@', '`',
+        ])
+        || text.contains(": ")
+        || text.contains(" #")
+        || text.contains(['\n', '\r'])
+        || (in_list && text.contains([',', '[', ']', '{', '}']));
+    if needs_quotes {
+        format!(
+            "\"{}\"",
+            text.replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+                .replace('\r', "\\r")
+        )
+    } else {
+        text.to_owned()
+    }
+}
+
+fn toml_value(key: &str, lines: &str) -> Option<FieldValue> {
+    let table: toml::Table = toml::from_str(lines).ok()?;
+    let text = |value: &toml::Value| match value {
+        toml::Value::String(text) => text.clone(),
+        value => value.to_string(),
+    };
+    Some(match table.get(key)? {
+        toml::Value::Array(items) => FieldValue::List(items.iter().map(text).collect()),
+        value => FieldValue::Text(text(value)),
+    })
+}
+
+fn toml_text(value: &FieldValue) -> String {
+    match value {
+        FieldValue::Text(text) => toml::Value::String(text.clone()).to_string(),
+        FieldValue::List(items) => toml::Value::Array(
+            items
+                .iter()
+                .map(|item| toml::Value::String(item.clone()))
+                .collect(),
+        )
+        .to_string(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // tests
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        let content = "---\ntitle: Retro\n---\n# Notes\n";
+        assert_eq!(
+            split(content),
+            Some(("---\ntitle: Retro\n---\n", "# Notes\n"))
+        );
+
+        let content = "+++\ntitle = \"Retro\"\n+++";
+        assert_eq!(split(content), Some((content, "")));
+    }
+
+    #[test]
+    fn test_no_front_matter() {
+        assert_eq!(split("# Notes\n---\n"), None);
+        assert_eq!(split("---\ntitle: Retro\n+++\n"), None);
+        assert_eq!(split("---"), None);
+    }
+
+    #[test]
+    fn test_yaml_fields() {
+        let content = "---\n# retro\ntitle: \"Retro: Q3\"\ntags:\n  - work\n  - 'team a'\naliases: [q3, \"a, b\"]\ncount: 3 # done\n---\nbody\n";
+        let (front_matter, body) = FrontMatter::parse(content).unwrap();
+        assert_eq!(body, "body\n");
+        let text = |text: &str| Some(FieldValue::Text(text.into()));
+        let list = |items: &[&str]| {
+            Some(FieldValue::List(
+                items.iter().map(|&item| item.into()).collect(),
+            ))
+        };
+        assert_eq!(front_matter.get("title"), text("Retro: Q3"));
+        assert_eq!(front_matter.get("tags"), list(&["work", "team a"]));
+        assert_eq!(front_matter.get("aliases"), list(&["q3", "a, b"]));
+        assert_eq!(front_matter.get("count"), text("3"));
+        assert_eq!(front_matter.get("retro"), None);
+        assert_eq!(front_matter.to_string() + body, content);
+    }
+
+    #[test]
+    fn test_toml_fields() {
+        let content =
+            "+++\ntitle = \"Retro\"\ntags = [\n  \"work\",\n]\n[extra]\nowner = \"me\"\n+++\n";
+        let (mut front_matter, _) = FrontMatter::parse(content).unwrap();
+        assert_eq!(
+            front_matter.get("title"),
+            Some(FieldValue::Text("Retro".into()))
+        );
+        assert_eq!(
+            front_matter.get("tags"),
+            Some(FieldValue::List(vec!["work".into()]))
+        );
+        assert_eq!(front_matter.get("owner"), None);
+
+        front_matter.set("status", &FieldValue::Text("draft".into()));
+        assert!(front_matter.unset("tags"));
+        assert!(!front_matter.unset("tags"));
+        assert_eq!(
+            front_matter.to_string(),
+            "+++\ntitle = \"Retro\"\nstatus = \"draft\"\n[extra]\nowner = \"me\"\n+++\n"
+        );
+    }
+
+    #[test]
+    fn test_set_yaml() {
+        let (mut front_matter, _) =
+            FrontMatter::parse("---\ntitle: Retro # q3\nb: 1\n---\n").unwrap();
+        front_matter.set("b", &FieldValue::new("b", vec!["x: y".into()]));
+        front_matter.set("tags", &FieldValue::new("tags", vec!["work".into()]));
+        front_matter.set(
+            "list",
+            &FieldValue::new("list", vec!["a,b".into(), "c".into()]),
+        );
+        assert_eq!(
+            front_matter.to_string(),
+            "---\ntitle: Retro # q3\nb: \"x: y\"\ntags: [work]\nlist: [\"a,b\", c]\n---\n"
+        );
+        assert_eq!(front_matter.get("b"), Some(FieldValue::Text("x: y".into())));
+        assert_eq!(
+            front_matter.get("list"),
+            Some(FieldValue::List(vec!["a,b".into(), "c".into()]))
+        );
+    }
+
+    #[test]
+    fn test_touch() {
+        assert_eq!(touch("# Notes\n", "now", true), None);
+        let content = touch(
+            "---\ntitle: Retro\n---\n",
+            "2026-10-18T10:00:00+02:00",
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            content,
+            "---\ntitle: Retro\ncreated: 2026-10-18T10:00:00+02:00\nupdated: 2026-10-18T10:00:00+02:00\n---\n"
+        );
+        let content = touch(&content, "later", false).unwrap();
+        assert!(content.contains("created: 2026-10-18T10:00:00+02:00\nupdated: later\n"));
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::file_operations::FileMetadata;
+
+/// Bumped whenever the on-disk format changes, older indexes are rebuilt.
+const INDEX_VERSION: u32 = 1;
+
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+
+/// Splits `content` into lowercase alphanumeric tokens with their line
+/// number, line numbers start at 1.
+pub fn tokenize(content: &str) -> impl Iterator<Item = (usize, String)> + '_ {
+    content.lines().enumerate().flat_map(|(i, line)| {
+        line.split(|c: char| !c.is_alphanumeric())
+            .filter(|token| !token.is_empty())
+            .map(move |token| (i + 1, token.to_lowercase()))
+    })
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct IndexedNote {
+    size: u64,
+    /// Modification time in microseconds since the unix epoch.
+    modified: i64,
+    token_count: usize,
+}
+
+impl IndexedNote {
+    fn is_stale(&self, metadata: &FileMetadata) -> bool {
+        self.size != metadata.size || self.modified != metadata.modified.timestamp_micros()
+    }
+}
+
+/// Inverted index over the notes of a notebook.
+///
+/// Notes are identified by the same labels the search output uses, so
+/// archived notes are kept apart from active notes with equal names.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SearchIndex {
+    version: u32,
+    notes: BTreeMap<String, IndexedNote>,
+    /// Token -> note label -> line number of every occurrence.
+    terms: BTreeMap<String, BTreeMap<String, Vec<usize>>>,
+}
+
+impl Default for SearchIndex {
+    fn default() -> Self {
+        Self {
+            version: INDEX_VERSION,
+            notes: BTreeMap::new(),
+            terms: BTreeMap::new(),
+        }
+    }
+}
+
+impl SearchIndex {
+    /// Parses a stored index, an unreadable or outdated index yields an empty
+    /// one which gets rebuilt on the next refresh.
+    pub fn from_json(json: &str) -> Self {
+        match serde_json::from_str::<Self>(json) {
+            Ok(index) if index.version == INDEX_VERSION => index,
+            _ => Self::default(),
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    pub fn len(&self) -> usize {
+        self.notes.len()
+    }
+
+    pub fn labels(&self) -> impl Iterator<Item = &String> {
+        self.notes.keys()
+    }
+
+    /// Whether the note has to be (re)indexed, because it is unknown or was
+    /// changed since it was indexed.
+    pub fn is_stale(&self, label: &str, metadata: &FileMetadata) -> bool {
+        self.notes
+            .get(label)
+            .is_none_or(|note| note.is_stale(metadata))
+    }
+
+    pub fn insert(&mut self, label: &str, content: &str, metadata: &FileMetadata) {
+        self.remove(label);
+        let mut token_count = 0;
+        for (line_number, token) in tokenize(content) {
+            token_count += 1;
+            self.terms
+                .entry(token)
+                .or_default()
+                .entry(label.to_owned())
+                .or_default()
+                .push(line_number);
+        }
+        let note = IndexedNote {
+            size: metadata.size,
+            modified: metadata.modified.timestamp_micros(),
+            token_count,
+        };
+        self.notes.insert(label.to_owned(), note);
+    }
+
+    pub fn remove(&mut self, label: &str) -> bool {
+        if self.notes.remove(label).is_none() {
+            return false;
+        }
+        self.terms.retain(|_, postings| {
+            postings.remove(label);
+            !postings.is_empty()
+        });
+        true
+    }
+
+    /// Notes containing every query token. With `whole_word` tokens have to
+    /// match indexed tokens exactly, otherwise it's enough if they are part
+    /// of one.
+    pub fn candidates(&self, query_tokens: &[String], whole_word: bool) -> BTreeSet<String> {
+        let mut candidates: Option<BTreeSet<String>> = None;
+        for query_token in query_tokens {
+            let notes: BTreeSet<String> = self
+                .terms
+                .iter()
+                .filter(|(term, _)| {
+                    if whole_word {
+                        *term == query_token
+                    } else {
+                        term.contains(query_token.as_str())
+                    }
+                })
+                .flat_map(|(_, postings)| postings.keys().cloned())
+                .collect();
+            candidates = Some(match candidates {
+                Some(candidates) => candidates.intersection(&notes).cloned().collect(),
+                None => notes,
+            });
+        }
+        candidates.unwrap_or_default()
+    }
+
+    /// BM25 score of every note containing at least one query token.
+    #[allow(clippy::cast_precision_loss)] // counts stay far below 2^52
+    pub fn scores(&self, query_tokens: &[String]) -> BTreeMap<String, f64> {
+        let mut scores = BTreeMap::new();
+        let note_count = self.notes.len() as f64;
+        if self.notes.is_empty() {
+            return scores;
+        }
+        let average_length = self
+            .notes
+            .values()
+            .map(|note| note.token_count as f64)
+            .sum::<f64>()
+            / note_count;
+        for query_token in query_tokens {
+            let Some(postings) = self.terms.get(query_token) else {
+                continue;
+            };
+            let document_frequency = postings.len() as f64;
+            let idf =
+                ((note_count - document_frequency + 0.5) / (document_frequency + 0.5)).ln_1p();
+            for (label, lines) in postings {
+                let Some(note) = self.notes.get(label) else {
+                    continue;
+                };
+                let term_frequency = lines.len() as f64;
+                let length_norm =
+                    1.0 - BM25_B + BM25_B * note.token_count as f64 / average_length.max(1.0);
+                let score = idf * term_frequency * (BM25_K1 + 1.0)
+                    / (term_frequency + BM25_K1 * length_norm);
+                *scores.entry(label.clone()).or_insert(0.0) += score;
+            }
+        }
+        scores
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // tests
+mod tests {
+    use chrono::{Local, TimeZone};
+
+    use super::*;
+
+    fn metadata(size: u64, modified: i64) -> FileMetadata {
+        let modified = Local.timestamp_opt(modified, 0).unwrap();
+        FileMetadata {
+            size,
+            created: modified,
+            modified,
+        }
+    }
+
+    fn tokens(query: &str) -> Vec<String> {
+        tokenize(query).map(|(_, token)| token).collect()
+    }
+
+    fn test_index() -> SearchIndex {
+        let mut index = SearchIndex::default();
+        index.insert("a", "Rust notes\nrust rust", &metadata(20, 1));
+        index.insert(
+            "b",
+            "Notes about cooking\nand rust removal",
+            &metadata(36, 1),
+        );
+        index.insert("c", "shopping list", &metadata(13, 1));
+        index
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let tokens: Vec<(usize, String)> = tokenize("Hello, World!\n\nfoo-bar").collect();
+        assert_eq!(
+            tokens,
+            [
+                (1, String::from("hello")),
+                (1, String::from("world")),
+                (3, String::from("foo")),
+                (3, String::from("bar")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_candidates() {
+        let index = test_index();
+        let candidates = index.candidates(&tokens("rust notes"), true);
+        assert_eq!(candidates, BTreeSet::from(["a".into(), "b".into()]));
+
+        let candidates = index.candidates(&tokens("cook"), true);
+        assert!(candidates.is_empty());
+
+        let candidates = index.candidates(&tokens("cook"), false);
+        assert_eq!(candidates, BTreeSet::from(["b".into()]));
+    }
+
+    #[test]
+    fn test_scores() {
+        let index = test_index();
+        let scores = index.scores(&tokens("rust"));
+        assert_eq!(scores.len(), 2);
+        assert!(scores.get("a").unwrap() > scores.get("b").unwrap());
+    }
+
+    #[test]
+    fn test_stale_and_remove() {
+        let mut index = test_index();
+        assert!(!index.is_stale("c", &metadata(13, 1)));
+        assert!(index.is_stale("c", &metadata(13, 2)));
+        assert!(index.is_stale("c", &metadata(14, 1)));
+        assert!(index.is_stale("d", &metadata(13, 1)));
+
+        assert!(index.remove("c"));
+        assert!(!index.remove("c"));
+        assert!(index.candidates(&tokens("shopping"), true).is_empty());
+        assert_eq!(index.len(), 2);
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let index = test_index();
+        assert_eq!(SearchIndex::from_json(&index.to_json().unwrap()), index);
+        assert_eq!(SearchIndex::from_json("{"), SearchIndex::default());
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Number of operations kept, older ones can't be undone anymore.
+const MAX_OPERATIONS: usize = 50;
+
+/// Total size of the file contents kept for undo and redo. Older operations
+/// are dropped beyond it, except for the last one.
+const MAX_BLOB_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Content of a file, stored once in the journal directory under its hash.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Blob {
+    pub hash: String,
+    pub size: u64,
+}
+
+impl Blob {
+    /// 64 bit FNV-1a hash of `content`, together with its length.
+    pub fn of(content: &[u8]) -> Self {
+        let hash = content
+            .iter()
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+            });
+        let size = content.len() as u64;
+        Self {
+            hash: format!("{hash:016x}-{size}"),
+            size,
+        }
+    }
+}
+
+/// Content of a file before and after an operation, `None` if the file
+/// didn't exist. The content before is stored when the operation runs, the
+/// content after only once the operation is undone, until then it's only
+/// compared with the file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub before: Option<Blob>,
+    pub after: Option<Blob>,
+}
+
+/// A mutating command and the files it changed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Operation {
+    pub description: String,
+    /// Time of the operation in seconds since the unix epoch.
+    pub time: i64,
+    pub changes: Vec<FileChange>,
+}
+
+/// Operations which can be undone, and undone operations which can be
+/// redone.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Journal {
+    pub done: Vec<Operation>,
+    /// The last undone operation is the first to be redone.
+    pub undone: Vec<Operation>,
+}
+
+impl Journal {
+    /// Adds a new operation, after which undone operations can't be redone
+    /// anymore.
+    pub fn push(&mut self, operation: Operation) {
+        self.done.push(operation);
+        self.undone.clear();
+        let excess = self.done.len().saturating_sub(MAX_OPERATIONS);
+        self.done.drain(..excess);
+        while self.done.len() > 1 && self.blob_size() > MAX_BLOB_SIZE {
+            self.done.remove(0);
+        }
+    }
+
+    /// Points the changes of files below `from` to their place below `to`,
+    /// returns whether any change was moved.
+    pub fn move_paths(&mut self, from: &Path, to: &Path) -> bool {
+        let mut moved = false;
+        let operations = self.done.iter_mut().chain(self.undone.iter_mut());
+        for change in operations.flat_map(|operation| operation.changes.iter_mut()) {
+            if let Ok(rest) = change.path.strip_prefix(from) {
+                change.path = to.join(rest);
+                moved = true;
+            }
+        }
+        moved
+    }
+
+    /// Stored contents the operations need, by hash with their size.
+    pub fn blobs(&self) -> BTreeMap<&str, u64> {
+        let done = self.done.iter().flat_map(|operation| {
+            operation
+                .changes
+                .iter()
+                .filter_map(|change| change.before.as_ref())
+        });
+        let undone = self.undone.iter().flat_map(|operation| {
+            operation
+                .changes
+                .iter()
+                .flat_map(|change| [&change.before, &change.after])
+                .flatten()
+        });
+        done.chain(undone)
+            .map(|blob| (blob.hash.as_str(), blob.size))
+            .collect()
+    }
+
+    fn blob_size(&self) -> u64 {
+        self.blobs().values().sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn operation(description: &str) -> Operation {
+        Operation {
+            description: description.into(),
+            time: 0,
+            changes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_push() {
+        let mut journal = Journal::default();
+        journal.push(operation("a"));
+        journal.undone.push(operation("b"));
+        journal.push(operation("c"));
+        assert_eq!(journal.done, [operation("a"), operation("c")]);
+        assert!(journal.undone.is_empty());
+    }
+
+    #[test]
+    fn test_push_limits_blob_size() {
+        let mut journal = Journal::default();
+        for size in [MAX_BLOB_SIZE / 2, MAX_BLOB_SIZE / 2, 1, MAX_BLOB_SIZE * 2] {
+            let mut operation = operation(&size.to_string());
+            operation.changes.push(FileChange {
+                path: PathBuf::from("/rn/notebook/log"),
+                before: Some(Blob {
+                    hash: format!("{size}-{}", journal.done.len()),
+                    size,
+                }),
+                after: None,
+            });
+            journal.push(operation);
+            if size == 1 {
+                // the oldest operation no longer fits
+                assert_eq!(journal.done.len(), 2);
+            }
+        }
+        // the last operation is kept even though it's too large on its own
+        assert_eq!(journal.done.len(), 1);
+        assert_eq!(journal.blobs().len(), 1);
+    }
+
+    #[test]
+    fn test_move_paths() {
+        let change = |path: &str| FileChange {
+            path: PathBuf::from(path),
+            before: None,
+            after: None,
+        };
+        let mut journal = Journal::default();
+        let mut operation = operation("rm");
+        operation.changes = vec![
+            change("/rn/notebooks/work/notebook/a"),
+            change("/rn/notebook/b"),
+        ];
+        journal.push(operation);
+        assert!(journal.move_paths(
+            Path::new("/rn/notebooks/work"),
+            Path::new("/rn/notebooks/job")
+        ));
+        let paths: Vec<&Path> = journal
+            .done
+            .iter()
+            .flat_map(|operation| &operation.changes)
+            .map(|change| change.path.as_path())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                Path::new("/rn/notebooks/job/notebook/a"),
+                Path::new("/rn/notebook/b")
+            ]
+        );
+        assert!(!journal.move_paths(
+            Path::new("/rn/notebooks/work"),
+            Path::new("/rn/notebooks/job")
+        ));
+    }
+
+    #[test]
+    fn test_blob_hash() {
+        assert_eq!(Blob::of(b"abc"), Blob::of(b"abc"));
+        assert_ne!(Blob::of(b"abc").hash, Blob::of(b"abd").hash);
+        assert_eq!(Blob::of(b"").hash, "cbf29ce484222325-0");
+    }
+
+    #[test]
+    fn test_push_drops_old_operations() {
+        let mut journal = Journal::default();
+        for i in 0..=MAX_OPERATIONS {
+            journal.push(operation(&i.to_string()));
+        }
+        assert_eq!(journal.done.len(), MAX_OPERATIONS);
+        assert_eq!(journal.done.first(), Some(&operation("1")));
+    }
+}
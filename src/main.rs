@@ -1,37 +1,67 @@
+use std::process::ExitCode;
+
 use anyhow::Result;
 use app::App;
 use clap::Parser;
 
-use crate::{cli::Cli, file_operations::FileSystem, message::Message};
+use crate::{
+    cli::{Cli, OutputFormat},
+    file_operations::FileSystem,
+    message::Message,
+    terminal::Terminal,
+};
 
 mod app;
+mod archive;
 mod cli;
 mod config;
 mod error;
 mod file_operations;
+mod front_matter;
+mod index;
+mod journal;
 mod message;
+mod output;
+mod picker;
+mod resolve;
+mod search;
+mod tags;
+mod template;
+mod terminal;
+mod trash;
 
 #[cfg(test)]
 mod mock_fs;
+#[cfg(test)]
+mod mock_terminal;
 
-fn main() {
-    let result = run();
-    print_result(result);
+fn main() -> ExitCode {
+    let command = Cli::parse();
+    let (format, quiet) = (command.format, command.quiet);
+    let result = run(command);
+    print_result(&result, format, quiet);
+    match &result {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(error) => ExitCode::from(error::exit_code(error)),
+    }
 }
 
-fn run() -> Result<Message> {
+fn run(command: Cli) -> Result<Message> {
     let fs = FileSystem;
-    let config = config::Config::build(&fs)?;
-    let mut app = App::new(config, fs)?;
-    let command = Cli::parse();
+    let mut config = config::Config::build(&fs)?;
+    if let Some(root_dir) = &command.root {
+        config.root_dir = Some(root_dir.clone());
+    }
+    let mut app = App::new(config, fs, Terminal)?;
     app.handle_command(command)
 }
 
-#[allow(clippy::print_stdout)] // global output of cli tool
-fn print_result(result: Result<Message>) {
+#[allow(clippy::print_stdout, clippy::print_stderr)] // global output of cli tool
+fn print_result(result: &Result<Message>, format: OutputFormat, quiet: bool) {
     match result {
-        Ok(m) => print!("{m}"),
-        Err(e) => print!("{e}"),
+        Ok(_) if quiet => {}
+        Ok(_) => print!("{}", output::render(result, format)),
+        Err(_) => eprint!("{}", output::render(result, format)),
     }
 }
 
@@ -1,51 +1,495 @@
-use std::{fmt::Display, path::PathBuf};
+use std::{collections::BTreeMap, fmt::Display, path::PathBuf};
 
-#[derive(Debug)]
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+use crate::front_matter::FieldValue;
+use crate::search::SearchMatch;
+
+const HIGHLIGHT_START: &str = "\x1b[1;31m";
+const HIGHLIGHT_END: &str = "\x1b[0m";
+/// Text format of the times in the trash, the archive history and the journal.
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Folder structure of `/` separated note paths, used to render `rn ls`.
+#[derive(Default)]
+struct TreeNode(BTreeMap<String, TreeNode>);
+
+impl TreeNode {
+    fn insert(&mut self, path: &str) {
+        let mut node = self;
+        for component in path.split('/') {
+            node = node.0.entry(component.to_owned()).or_default();
+        }
+    }
+
+    /// Top level entries are written without connectors, so a notebook
+    /// without folders renders as a plain list.
+    fn write(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        prefix: &str,
+        top_level: bool,
+    ) -> std::fmt::Result {
+        let count = self.0.len();
+        for (i, (name, child)) in self.0.iter().enumerate() {
+            let last = i + 1 == count;
+            let (connector, child_prefix) = match (top_level, last) {
+                (true, _) => ("", String::new()),
+                (false, true) => ("└── ", format!("{prefix}    ")),
+                (false, false) => ("├── ", format!("{prefix}│   ")),
+            };
+            let folder_marker = if child.0.is_empty() { "" } else { "/" };
+            writeln!(f, "{prefix}{connector}{name}{folder_marker}")?;
+            child.write(f, &child_prefix, false)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_search_line(
+    f: &mut std::fmt::Formatter<'_>,
+    search_match: &SearchMatch,
+    highlight: bool,
+) -> std::fmt::Result {
+    let line = search_match.line.as_str();
+    if !highlight {
+        return write!(f, "{line}");
+    }
+    let mut position = 0;
+    for range in &search_match.ranges {
+        let before = line.get(position..range.start).unwrap_or_default();
+        let matched = line.get(range.clone()).unwrap_or_default();
+        write!(f, "{before}{HIGHLIGHT_START}{matched}{HIGHLIGHT_END}")?;
+        position = range.end;
+    }
+    write!(f, "{}", line.get(position..).unwrap_or_default())
+}
+
+/// A listed note, the text output only shows its name unless it's a long
+/// listing.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NoteInfo {
+    pub name: String,
+    pub path: PathBuf,
+    /// Size in bytes, compressed archived notes have their compressed size.
+    pub size: u64,
+    pub created: DateTime<Local>,
+    pub modified: DateTime<Local>,
+    /// Only counted for long listings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NoteContent {
+    pub name: String,
+    pub content: String,
+}
+
+/// A note in the trash and the notebook and name it had.
+#[derive(Debug, Serialize)]
+pub struct TrashedNote {
+    pub id: String,
+    pub deleted: DateTime<Local>,
+    pub location: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigValue {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestoredNote {
+    pub from: String,
+    pub to: String,
+}
+
+/// A version of an archived note, numbered from 1 for the oldest.
+#[derive(Debug, Serialize)]
+pub struct ArchivedVersion {
+    pub version: usize,
+    pub archived: DateTime<Local>,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NotebookInfo {
+    pub name: String,
+    pub current: bool,
+}
+
+/// An operation of the journal.
+#[derive(Debug, Serialize)]
+pub struct HistoryEntry {
+    pub time: DateTime<Local>,
+    pub description: String,
+    pub undone: bool,
+}
+
+/// Serialized with the variant name as `type` and its fields, which are
+/// described in the README.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum Message {
-    Notebook(Vec<String>),
-    Archive(Vec<String>),
+    #[serde(rename = "notes")]
+    Notebook {
+        notes: Vec<NoteInfo>,
+    },
+    NoteTree {
+        notes: Vec<NoteInfo>,
+    },
+    /// Notes with their size, line count and modification time.
+    #[serde(rename = "notes")]
+    LongList {
+        notes: Vec<NoteInfo>,
+    },
+    Archive {
+        notes: Vec<NoteInfo>,
+    },
     CreatedNote,
+    AppendedNote {
+        name: String,
+    },
+    MovedNote {
+        from: String,
+        to: String,
+    },
+    CopiedNote {
+        from: String,
+        to: String,
+    },
     DeletedNote,
-    CompletionScript(String),
-    ConfigValues(Vec<(String, String)>),
-    GeneratedConfig(PathBuf),
-    ArchivedNote((String, String)),
-    RestoredNote((String, String)),
+    DeletedNotes {
+        names: Vec<String>,
+    },
+    TrashedNotes {
+        names: Vec<String>,
+    },
+    Trash {
+        entries: Vec<TrashedNote>,
+    },
+    EmptiedTrash {
+        count: usize,
+    },
+    NoteContents {
+        notes: Vec<NoteContent>,
+    },
+    CompletionScript {
+        script: String,
+    },
+    ConfigValues {
+        values: Vec<ConfigValue>,
+    },
+    GeneratedConfig {
+        path: PathBuf,
+    },
+    ArchivedNote {
+        from: String,
+        to: String,
+    },
+    Snapshot {
+        from: String,
+        to: String,
+    },
+    /// Key and value of a front matter field.
+    MetaValue {
+        key: String,
+        value: FieldValue,
+    },
+    /// Note and key of a changed front matter field.
+    SetMeta {
+        name: String,
+        key: String,
+    },
+    UnsetMeta {
+        name: String,
+        key: String,
+    },
+    /// Note and the tags added to or removed from it.
+    TaggedNote {
+        name: String,
+        tags: Vec<String>,
+    },
+    UntaggedNote {
+        name: String,
+        tags: Vec<String>,
+    },
+    Tags {
+        tags: Vec<TagCount>,
+    },
+    RestoredNote {
+        from: String,
+        to: String,
+    },
+    RestoredNotes {
+        notes: Vec<RestoredNote>,
+    },
+    /// Versions of an archived note, oldest first.
+    ArchiveHistory {
+        versions: Vec<ArchivedVersion>,
+    },
+    Diff {
+        diff: String,
+    },
+    PrunedArchive {
+        dry_run: bool,
+        names: Vec<String>,
+    },
+    /// Number of compressed notes and their size in bytes before and after.
+    CompactedArchive {
+        count: usize,
+        size: usize,
+        compressed_size: usize,
+    },
+    Notebooks {
+        notebooks: Vec<NotebookInfo>,
+    },
+    Templates {
+        names: Vec<String>,
+    },
+    CreatedTemplate {
+        name: String,
+    },
+    DeletedTemplate {
+        name: String,
+    },
+    CreatedNotebook {
+        name: String,
+    },
+    DeletedNotebook {
+        name: String,
+    },
+    RenamedNotebook {
+        from: String,
+        to: String,
+    },
+    UsingNotebook {
+        name: String,
+    },
+    /// Search matches and whether to highlight them with ANSI escape codes.
+    SearchResults {
+        matches: Vec<SearchMatch>,
+        #[serde(skip)]
+        highlight: bool,
+    },
+    RebuiltIndex {
+        count: usize,
+    },
+    Undone {
+        description: String,
+    },
+    Redone {
+        description: String,
+    },
+    /// Operations of the journal, oldest first.
+    History {
+        operations: Vec<HistoryEntry>,
+    },
     Empty,
 }
+
 impl Display for Message {
+    #[allow(clippy::too_many_lines)] // one arm per message
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::CreatedNote => {
                 writeln!(f, "Created note")
             }
+            Self::AppendedNote { name } => {
+                writeln!(f, "Appended to note {name}")
+            }
+            Self::MovedNote { from, to } => {
+                writeln!(f, "Moved note {from} to {to}")
+            }
+            Self::CopiedNote { from, to } => {
+                writeln!(f, "Copied note {from} to {to}")
+            }
             Self::DeletedNote => {
                 writeln!(f, "Deleted note")
             }
-            Self::Notebook(notes) | Self::Archive(notes) => {
-                for name in notes {
+            Self::NoteContents { notes } => {
+                let with_headers = notes.len() > 1;
+                for (i, NoteContent { name, content }) in notes.iter().enumerate() {
+                    if with_headers {
+                        if i > 0 {
+                            writeln!(f)?;
+                        }
+                        writeln!(f, "==> {name} <==")?;
+                    }
+                    write!(f, "{content}")?;
+                    if !content.is_empty() && !content.ends_with('\n') {
+                        writeln!(f)?;
+                    }
+                }
+                Ok(())
+            }
+            Self::TrashedNotes { names } => {
+                for name in names {
+                    writeln!(f, "Moved note {name} to the trash")?;
+                }
+                Ok(())
+            }
+            Self::Trash { entries } => {
+                for entry in entries {
+                    let deleted = entry.deleted.format(TIME_FORMAT);
+                    writeln!(f, "{}  {deleted}  {}", entry.id, entry.location)?;
+                }
+                Ok(())
+            }
+            Self::EmptiedTrash { count } => {
+                writeln!(f, "Deleted {count} notes from the trash")
+            }
+            Self::DeletedNotes { names } => {
+                for name in names {
+                    writeln!(f, "Deleted note {name}")?;
+                }
+                Ok(())
+            }
+            Self::Notebook { notes } | Self::Archive { notes } => {
+                for note in notes {
+                    writeln!(f, "{}", note.name)?;
+                }
+                Ok(())
+            }
+            Self::Templates { names } => {
+                for name in names {
                     writeln!(f, "{name}")?;
                 }
                 Ok(())
             }
-            Self::CompletionScript(script) => writeln!(f, "{script}"),
-            Self::ConfigValues(config_values) => {
-                let name_col_width = config_values
+            Self::LongList { notes } => {
+                for note in notes {
+                    writeln!(
+                        f,
+                        "{:>8} {:>6} {} {}",
+                        note.size,
+                        note.lines.unwrap_or_default(),
+                        note.modified.format("%Y-%m-%d %H:%M"),
+                        note.name
+                    )?;
+                }
+                Ok(())
+            }
+            Self::NoteTree { notes } => {
+                let mut root = TreeNode::default();
+                for note in notes {
+                    root.insert(&note.name);
+                }
+                root.write(f, "", true)
+            }
+            Self::CompletionScript { script } => writeln!(f, "{script}"),
+            Self::ConfigValues { values } => {
+                let name_col_width = values
                     .iter()
-                    .map(|(n, _)| n.len())
+                    .map(|value| value.name.len())
                     .max()
                     .unwrap_or(0);
-                for (name, value) in config_values {
+                for ConfigValue { name, value } in values {
                     writeln!(f, "{name:<name_col_width$} : {value}")?;
                 }
                 Ok(())
             }
-            Self::GeneratedConfig(path) => writeln!(f, "Generated config file {}", path.display()),
-            Self::ArchivedNote((original_name, archived_name)) => {
-                writeln!(f, "Archived note {original_name} to {archived_name}")
+            Self::GeneratedConfig { path } => {
+                writeln!(f, "Generated config file {}", path.display())
+            }
+            Self::ArchivedNote { from, to } => {
+                writeln!(f, "Archived note {from} to {to}")
+            }
+            Self::Snapshot { from, to } => {
+                writeln!(f, "Saved snapshot of note {from} as {to}")
+            }
+            Self::MetaValue { value, .. } => write!(f, "{value}"),
+            Self::SetMeta { name, key } => writeln!(f, "Set {key} of note {name}"),
+            Self::UnsetMeta { name, key } => writeln!(f, "Removed {key} from note {name}"),
+            Self::TaggedNote { name, tags } => {
+                writeln!(f, "Tagged note {name} with {}", tags.join(", "))
+            }
+            Self::UntaggedNote { name, tags } => {
+                writeln!(f, "Removed tags {} from note {name}", tags.join(", "))
+            }
+            Self::Tags { tags } => {
+                for TagCount { tag, count } in tags {
+                    writeln!(f, "{tag} ({count})")?;
+                }
+                Ok(())
+            }
+            Self::RestoredNote { from, to } => {
+                writeln!(f, "Restored note {from} to {to}")
+            }
+            Self::ArchiveHistory { versions } => {
+                for version in versions {
+                    let archived = version.archived.format(TIME_FORMAT);
+                    writeln!(f, "{}  {archived}  {}", version.version, version.name)?;
+                }
+                Ok(())
+            }
+            Self::Diff { diff } => write!(f, "{diff}"),
+            Self::PrunedArchive { names, .. } if names.is_empty() => {
+                writeln!(f, "No archived notes to prune")
             }
-            Self::RestoredNote((archived_name, new_name)) => {
-                writeln!(f, "Restored note {archived_name} to {new_name}")
+            Self::CompactedArchive {
+                count,
+                size,
+                compressed_size,
+            } => writeln!(
+                f,
+                "Compressed {count} archived notes from {size} to {compressed_size} bytes"
+            ),
+            Self::PrunedArchive { dry_run, names } => {
+                let action = if *dry_run { "Would delete" } else { "Deleted" };
+                for name in names {
+                    writeln!(f, "{action} archived note {name}")?;
+                }
+                Ok(())
+            }
+            Self::RestoredNotes { notes } => {
+                for RestoredNote { from, to } in notes {
+                    writeln!(f, "Restored note {from} to {to}")?;
+                }
+                Ok(())
+            }
+            Self::Notebooks { notebooks } => {
+                for NotebookInfo { name, current } in notebooks {
+                    let marker = if *current { '*' } else { ' ' };
+                    writeln!(f, "{marker} {name}")?;
+                }
+                Ok(())
+            }
+            Self::CreatedTemplate { name } => writeln!(f, "Created template {name}"),
+            Self::DeletedTemplate { name } => writeln!(f, "Deleted template {name}"),
+            Self::CreatedNotebook { name } => writeln!(f, "Created notebook {name}"),
+            Self::DeletedNotebook { name } => writeln!(f, "Deleted notebook {name}"),
+            Self::RenamedNotebook { from, to } => {
+                writeln!(f, "Renamed notebook {from} to {to}")
+            }
+            Self::UsingNotebook { name } => writeln!(f, "Using notebook {name}"),
+            Self::SearchResults { matches, highlight } => {
+                for search_match in matches {
+                    write!(f, "{}:{}: ", search_match.note, search_match.line_number)?;
+                    write_search_line(f, search_match, *highlight)?;
+                    writeln!(f)?;
+                }
+                Ok(())
+            }
+            Self::RebuiltIndex { count } => writeln!(f, "Indexed {count} notes"),
+            Self::Undone { description } => writeln!(f, "Undid {description}"),
+            Self::Redone { description } => writeln!(f, "Redid {description}"),
+            Self::History { operations } => {
+                for operation in operations {
+                    let time = operation.time.format(TIME_FORMAT);
+                    let state = if operation.undone { "  (undone)" } else { "" };
+                    writeln!(f, "{time}  {}{state}", operation.description)?;
+                }
+                Ok(())
             }
             Self::Empty => Ok(()),
         }
@@ -1,91 +1,154 @@
 #![allow(clippy::unwrap_used)] // test-only
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
 use std::path::PathBuf;
 
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, TimeZone};
 
-use crate::file_operations::FileOperations;
+use crate::file_operations::{FileMetadata, FileOperations};
 
-fn extract_file_name(root_dir_path: &Path, file_path: &Path) -> Result<String> {
-    if !file_path.starts_with(root_dir_path) {
-        return Err(anyhow!("File not in rn root directory"));
-    }
-    let root_dir_path_len = root_dir_path.iter().count();
-    let file_path_len = file_path.iter().count();
-    if root_dir_path_len + 1 != file_path_len {
-        return Err(anyhow!("Path does not point to file in rn root directory"));
-    }
-    let file_name = file_path.file_name().unwrap().to_str().unwrap().to_owned();
-    Ok(file_name)
+fn is_hidden(name: &str) -> bool {
+    name.starts_with('.')
+}
+
+#[derive(Clone)]
+struct MockFile {
+    content: Vec<u8>,
+    created: DateTime<Local>,
+    modified: DateTime<Local>,
 }
 
+/// In-memory file system rooted at `rn_root_dir`, every path outside of it is
+/// rejected.
+///
+/// Creation and modification times come from a clock that advances one second
+/// with every write, so they are deterministic.
 #[allow(unused)]
 pub struct MockFileSystem {
     opened_files: Vec<PathBuf>,
     rn_root_dir: PathBuf,
-    files: Vec<String>,
+    files: BTreeMap<PathBuf, MockFile>,
+    dirs: BTreeSet<PathBuf>,
+    clock: i64,
+    edit: String,
 }
 
 #[allow(unused)]
 impl MockFileSystem {
-    pub fn new(rn_root_dir: PathBuf, notes: Vec<String>) -> Self {
-        Self {
+    /// `notes` are paths relative to `rn_root_dir`, they are created empty
+    /// together with their parent directories.
+    pub fn new(rn_root_dir: PathBuf, notes: &[&str]) -> Self {
+        let mut mock_fs = Self {
             opened_files: Vec::new(),
-            rn_root_dir,
-            files: notes,
+            rn_root_dir: rn_root_dir.clone(),
+            files: BTreeMap::new(),
+            dirs: BTreeSet::from([rn_root_dir]),
+            clock: 0,
+            edit: String::new(),
+        };
+        for note in notes {
+            let path = mock_fs.rn_root_dir.join(note);
+            mock_fs.create_dir(path.parent().unwrap()).unwrap();
+            mock_fs.write_file(&path, "").unwrap();
         }
+        mock_fs
     }
 
     pub fn opened_files(&self) -> &Vec<PathBuf> {
         &self.opened_files
     }
 
-    fn is_file(&self, path: &Path) -> bool {
-        if let Ok(file_name) = extract_file_name(&self.rn_root_dir, path) {
-            if self.files.contains(&file_name) {
-                return true;
-            }
+    /// Text the editor appends to the next opened file.
+    pub fn set_edit(&mut self, edit: &str) {
+        self.edit = edit.to_owned();
+    }
+
+    pub fn is_file(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+
+    pub fn is_dir(&self, path: &Path) -> bool {
+        self.dirs.contains(path)
+    }
+
+    fn tick(&mut self) -> DateTime<Local> {
+        self.clock += 1;
+        Local.timestamp_opt(self.clock, 0).unwrap()
+    }
+
+    fn check_in_root(&self, path: &Path) -> Result<()> {
+        if !path.starts_with(&self.rn_root_dir) {
+            return Err(anyhow!("File not in rn root directory"));
         }
-        false
+        Ok(())
     }
 
-    fn is_dir(&self, path: &Path) -> bool {
-        if *path == self.rn_root_dir {
-            return true;
+    fn check_parent_dir(&self, path: &Path) -> Result<()> {
+        self.check_in_root(path)?;
+        match path.parent() {
+            Some(parent) if self.is_dir(parent) => Ok(()),
+            _ => Err(anyhow!("Parent directory does not exist")),
         }
-        false
     }
 }
 
 impl FileOperations for MockFileSystem {
     fn get_files(&self, dir: &Path) -> Result<Vec<String>> {
-        if *dir == self.rn_root_dir {
-            return Ok(self.files.clone());
+        if !self.is_dir(dir) {
+            return Err(anyhow!("Directory does not exist"));
         }
-        Err(anyhow!("Directory does not exist"))
+        let children = self
+            .files
+            .keys()
+            .chain(self.dirs.iter())
+            .filter(|path| path.parent() == Some(dir))
+            .map(|path| path.file_name().unwrap().to_str().unwrap().to_owned())
+            .filter(|name| !is_hidden(name))
+            .collect();
+        Ok(children)
     }
 
-    fn delete_file(&mut self, path: &Path) -> Result<()> {
-        let file_name = extract_file_name(&self.rn_root_dir, path)?;
-        let file_index = self.files.iter().position(|f| *f == file_name);
-        if let Some(file_index) = file_index {
-            let _ = self.files.remove(file_index);
-            return Ok(());
+    fn get_files_recursive(&self, dir: &Path) -> Result<Vec<String>> {
+        if !self.is_dir(dir) {
+            return Err(anyhow!("Directory does not exist"));
         }
-        Err(anyhow!("File does not exist"))
+        let files = self
+            .files
+            .keys()
+            .filter_map(|path| path.strip_prefix(dir).ok())
+            .map(|path| path.to_str().unwrap().to_owned())
+            .filter(|name| !name.split('/').any(is_hidden))
+            .collect();
+        Ok(files)
+    }
+
+    fn delete_file(&mut self, path: &Path) -> Result<()> {
+        self.files
+            .remove(path)
+            .map(|_| ())
+            .ok_or(anyhow!("File does not exist"))
     }
 
     fn create_file(&mut self, path: &Path) -> Result<()> {
-        extract_file_name(&self.rn_root_dir, path).map(|file_name| {
-            self.files.push(file_name);
-        })
+        if self.is_file(path) || self.is_dir(path) {
+            return Err(anyhow!("File already exists"));
+        }
+        self.write_file(path, "")
     }
 
     fn create_dir(&mut self, path: &Path) -> Result<()> {
-        if *path == self.rn_root_dir {
-            return Err(anyhow!("{:?} already exists", self.rn_root_dir));
+        self.check_in_root(path)?;
+        for dir in path.ancestors() {
+            if self.is_file(dir) {
+                return Err(anyhow!("Can't create directory, because a file exists"));
+            }
+            self.dirs.insert(dir.to_path_buf());
+            if dir == self.rn_root_dir {
+                break;
+            }
         }
-        Err(anyhow!("can't create directories in mock fs"))
+        Ok(())
     }
 
     fn open_file(&mut self, _editor_command: &str, path: &Path) -> Result<()> {
@@ -93,25 +156,114 @@ impl FileOperations for MockFileSystem {
             return Err(anyhow!("Can't open because its not a file"));
         }
         self.opened_files.push(PathBuf::from(path));
+        let edit = std::mem::take(&mut self.edit);
+        if !edit.is_empty() {
+            self.append_file(path, &edit)?;
+        }
         Ok(())
     }
 
     fn exists(&self, path: &Path) -> Result<bool> {
-        if self.is_dir(path) || self.is_file(path) {
-            return Ok(true);
+        Ok(self.is_dir(path) || self.is_file(path))
+    }
+
+    fn read_file(&self, path: &Path) -> Result<String> {
+        Ok(String::from_utf8(self.read_bytes(path)?)?)
+    }
+
+    fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        self.files
+            .get(path)
+            .map(|file| file.content.clone())
+            .ok_or(anyhow!("File does not exist"))
+    }
+
+    fn write_file(&mut self, path: &Path, value: &str) -> Result<()> {
+        self.write_bytes(path, value.as_bytes())
+    }
+
+    fn write_bytes(&mut self, path: &Path, value: &[u8]) -> Result<()> {
+        self.check_parent_dir(path)?;
+        let modified = self.tick();
+        let file = MockFile {
+            content: value.to_owned(),
+            created: self.files.get(path).map_or(modified, |file| file.created),
+            modified,
+        };
+        self.files.insert(path.to_path_buf(), file);
+        Ok(())
+    }
+
+    fn append_file(&mut self, path: &Path, value: &str) -> Result<()> {
+        if self.is_dir(path) {
+            return Err(anyhow!("Is a directory"));
+        }
+        let mut content = self
+            .files
+            .get(path)
+            .map(|file| file.content.clone())
+            .unwrap_or_default();
+        content.extend_from_slice(value.as_bytes());
+        self.write_bytes(path, &content)
+    }
+
+    fn copy(&mut self, source_path: &Path, destination_path: &Path) -> Result<()> {
+        let content = self.read_bytes(source_path)?;
+        self.write_bytes(destination_path, &content)
+    }
+
+    fn rename(&mut self, source_path: &Path, destination_path: &Path) -> Result<()> {
+        self.check_parent_dir(destination_path)?;
+        if let Some(file) = self.files.remove(source_path) {
+            self.files.insert(destination_path.to_path_buf(), file);
+            return Ok(());
+        }
+        if !self.is_dir(source_path) {
+            return Err(anyhow!("File does not exist"));
         }
-        Ok(false)
+        let move_path = |path: &PathBuf| match path.strip_prefix(source_path) {
+            Ok(rest) => destination_path.join(rest),
+            Err(_) => path.clone(),
+        };
+        self.files = self
+            .files
+            .iter()
+            .map(|(path, file)| (move_path(path), file.clone()))
+            .collect();
+        self.dirs = self.dirs.iter().map(move_path).collect();
+        Ok(())
     }
 
-    fn read_file(&self, _path: &Path) -> Result<String> {
-        Err(anyhow!("Can't read from file in mock file system"))
+    fn delete_dir(&mut self, path: &Path) -> Result<bool> {
+        if !self.is_dir(path) {
+            return Err(anyhow!("Directory does not exist"));
+        }
+        let is_empty = !self.files.keys().any(|file| file.starts_with(path))
+            && !self
+                .dirs
+                .iter()
+                .any(|dir| dir != path && dir.starts_with(path));
+        if is_empty {
+            self.dirs.remove(path);
+        }
+        Ok(is_empty)
     }
 
-    fn write_file(&mut self, _path: &Path, _value: &str) -> Result<()> {
-        Err(anyhow!("Can't write to file in mock file system"))
+    fn delete_dir_all(&mut self, path: &Path) -> Result<()> {
+        if !self.is_dir(path) {
+            return Err(anyhow!("Directory does not exist"));
+        }
+        self.files.retain(|file, _| !file.starts_with(path));
+        self.dirs.retain(|dir| !dir.starts_with(path));
+        Ok(())
     }
 
-    fn copy(&mut self, _source_path: &Path, _destination_path: &Path) -> Result<()> {
-        Err(anyhow!("Can't copy files in mock file system"))
+    fn metadata(&self, path: &Path) -> Result<FileMetadata> {
+        let file = self.files.get(path).ok_or(anyhow!("File does not exist"))?;
+        Ok(FileMetadata {
+            size: file.content.len() as u64,
+            created: file.created,
+            modified: file.modified,
+        })
     }
 }
@@ -0,0 +1,66 @@
+use anyhow::Result;
+
+use crate::terminal::TerminalOperations;
+
+/// Terminal with scripted input which records what it shows.
+#[derive(Default)]
+pub struct MockTerminal {
+    picked: Vec<String>,
+    terminal: bool,
+    paged: Vec<String>,
+    stdin: String,
+}
+
+impl MockTerminal {
+    /// Names the user picks the next time the picker is shown.
+    pub fn set_picked(&mut self, picked: &[&str]) {
+        self.picked = picked.iter().map(ToString::to_string).collect();
+    }
+
+    /// Whether stdout is treated as a terminal, which enables paging and
+    /// colors.
+    pub fn set_terminal(&mut self, terminal: bool) {
+        self.terminal = terminal;
+    }
+
+    pub fn set_stdin(&mut self, stdin: &str) {
+        self.stdin = stdin.to_owned();
+    }
+
+    pub fn paged(&self) -> &Vec<String> {
+        &self.paged
+    }
+}
+
+impl TerminalOperations for MockTerminal {
+    fn read_stdin(&mut self) -> Result<String> {
+        Ok(std::mem::take(&mut self.stdin))
+    }
+
+    fn pick(
+        &mut self,
+        names: &[String],
+        multi: bool,
+        _preview: impl Fn(&str) -> String,
+    ) -> Result<Vec<String>> {
+        let mut picked: Vec<String> = std::mem::take(&mut self.picked)
+            .into_iter()
+            .filter(|name| names.contains(name))
+            .collect();
+        if !multi {
+            picked.truncate(1);
+        }
+        Ok(picked)
+    }
+
+    fn page(&mut self, _pager_command: &str, content: &str) -> Result<bool> {
+        if self.terminal {
+            self.paged.push(content.to_owned());
+        }
+        Ok(self.terminal)
+    }
+
+    fn use_color(&self) -> bool {
+        self.terminal
+    }
+}
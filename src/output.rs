@@ -0,0 +1,192 @@
+use anyhow::{Error, Result};
+use serde_json::{json, Map, Value};
+
+use crate::cli::OutputFormat;
+use crate::error::{AppError, FileSystemError, InternalError, SystemError};
+use crate::message::Message;
+
+/// Renders the result of a command in the requested format.
+pub fn render(result: &Result<Message>, format: OutputFormat) -> String {
+    match (format, result) {
+        (OutputFormat::Text, Ok(message)) => message.to_string(),
+        (OutputFormat::Text, Err(error)) => error.to_string(),
+        (OutputFormat::Json, result) => format!("{}\n", to_json(result)),
+        (OutputFormat::Tsv, result) => to_tsv(&to_json(result)),
+    }
+}
+
+fn to_json(result: &Result<Message>) -> Value {
+    match result {
+        Ok(message) => serde_json::to_value(message).unwrap_or_default(),
+        Err(error) => error_to_json(error),
+    }
+}
+
+/// Errors have the type `error`, a `kind`, the `details` of an `AppError` and
+/// the text `message`.
+fn error_to_json(error: &Error) -> Value {
+    let mut fields = Map::new();
+    fields.insert("type".into(), json!("error"));
+    if let Some(app_error) = error.downcast_ref::<AppError>() {
+        if let Ok(Value::Object(app_error)) = serde_json::to_value(app_error) {
+            fields.extend(app_error);
+        }
+    } else {
+        let kind = if error.is::<SystemError>() {
+            "system"
+        } else if error.is::<FileSystemError>() {
+            "file_system"
+        } else if error.is::<InternalError>() {
+            "internal"
+        } else {
+            "io"
+        };
+        fields.insert("kind".into(), json!(kind));
+    }
+    fields.insert("message".into(), json!(error.to_string().trim_end()));
+    Value::Object(fields)
+}
+
+/// One line per item of the list in the JSON message, or a single line with
+/// its fields if it has no list. The `type` is left out.
+fn to_tsv(value: &Value) -> String {
+    let Value::Object(fields) = value else {
+        return String::new();
+    };
+    let fields: Vec<&Value> = fields
+        .iter()
+        .filter(|(name, _)| *name != "type")
+        .map(|(_, value)| value)
+        .collect();
+    if fields.is_empty() {
+        return String::new();
+    }
+    let rows: Vec<String> = match fields.iter().find_map(|value| value.as_array()) {
+        Some(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::Object(item) => tsv_row(item.values()),
+                item => tsv_field(item),
+            })
+            .collect(),
+        None => vec![tsv_row(fields.into_iter())],
+    };
+    rows.into_iter().map(|row| row + "\n").collect()
+}
+
+fn tsv_row<'a>(values: impl Iterator<Item = &'a Value>) -> String {
+    values.map(tsv_field).collect::<Vec<_>>().join("\t")
+}
+
+/// Strings are written as they are, except for escaped tabs, newlines and
+/// backslashes. Lists and objects are written as JSON.
+fn tsv_field(value: &Value) -> String {
+    let text = match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    };
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // tests
+mod tests {
+    use super::*;
+    use crate::message::{HistoryEntry, NoteContent, TrashedNote};
+    use chrono::{Local, SecondsFormat, TimeZone};
+
+    #[test]
+    fn test_json() {
+        let message = Message::MovedNote {
+            from: "a".into(),
+            to: "b".into(),
+        };
+        assert_eq!(
+            render(&Ok(message), OutputFormat::Json),
+            "{\"type\":\"moved_note\",\"from\":\"a\",\"to\":\"b\"}\n"
+        );
+        let error = AppError::Ambiguous("to".into(), vec!["todo".into(), "tools".into()]);
+        let mut json: Value =
+            serde_json::from_str(&render(&Err(error.into()), OutputFormat::Json)).unwrap();
+        let text = json.as_object_mut().unwrap().remove("message").unwrap();
+        assert!(text.as_str().unwrap().starts_with("\"to\" matches"));
+        assert_eq!(
+            json,
+            json!({"type": "error", "kind": "ambiguous", "details": ["to", ["todo", "tools"]]})
+        );
+    }
+
+    #[test]
+    fn test_json_times_are_rfc_3339() {
+        let time = Local.timestamp_opt(90, 0).unwrap();
+        let rfc_3339 = json!(time.to_rfc3339_opts(SecondsFormat::AutoSi, true));
+        let message = Message::Trash {
+            entries: vec![TrashedNote {
+                id: "1".into(),
+                deleted: time,
+                location: "default:todo".into(),
+            }],
+        };
+        let json: Value = serde_json::from_str(&render(&Ok(message), OutputFormat::Json)).unwrap();
+        assert_eq!(
+            json,
+            json!({"type": "trash", "entries": [
+                {"id": "1", "deleted": rfc_3339, "location": "default:todo"}
+            ]})
+        );
+        let message = Message::History {
+            operations: vec![HistoryEntry {
+                time,
+                description: "rm todo".into(),
+                undone: false,
+            }],
+        };
+        let json: Value = serde_json::from_str(&render(&Ok(message), OutputFormat::Json)).unwrap();
+        assert_eq!(
+            json,
+            json!({"type": "history", "operations": [
+                {"time": rfc_3339, "description": "rm todo", "undone": false}
+            ]})
+        );
+    }
+
+    #[test]
+    fn test_tsv() {
+        let notes = vec![
+            NoteContent {
+                name: "a".into(),
+                content: "one\ttwo\nthree\n".into(),
+            },
+            NoteContent {
+                name: "b".into(),
+                content: String::new(),
+            },
+        ];
+        let message = Message::NoteContents { notes };
+        assert_eq!(
+            render(&Ok(message), OutputFormat::Tsv),
+            "a\tone\\ttwo\\nthree\\n\nb\t\n"
+        );
+        let message = Message::CompactedArchive {
+            count: 2,
+            size: 100,
+            compressed_size: 40,
+        };
+        assert_eq!(render(&Ok(message), OutputFormat::Tsv), "2\t100\t40\n");
+        assert_eq!(render(&Ok(Message::Empty), OutputFormat::Tsv), "");
+    }
+
+    #[test]
+    fn test_text_is_unchanged() {
+        let message = Message::MovedNote {
+            from: "a".into(),
+            to: "b".into(),
+        };
+        let text = message.to_string();
+        assert_eq!(render(&Ok(message), OutputFormat::Text), text);
+    }
+}
@@ -0,0 +1,361 @@
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+
+use anyhow::Result;
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+
+use crate::resolve::fuzzy_score;
+
+const PROMPT: &str = "> ";
+const SELECTED_MARKER: &str = "* ";
+const UNSELECTED_MARKER: &str = "  ";
+const TAB_WIDTH: usize = 4;
+
+#[derive(Debug, PartialEq)]
+enum Action {
+    Continue,
+    Accept,
+    Cancel,
+}
+
+/// Everything of the picker which doesn't touch the terminal.
+struct PickerState<'a> {
+    items: &'a [String],
+    multi: bool,
+    query: String,
+    /// Indices into `items` matching `query`, best match first.
+    filtered: Vec<usize>,
+    /// Position of the cursor in `filtered`.
+    cursor: usize,
+    /// First position of `filtered` which is visible.
+    scroll: usize,
+    selected: BTreeSet<usize>,
+}
+
+impl<'a> PickerState<'a> {
+    fn new(items: &'a [String], multi: bool) -> Self {
+        let mut state = Self {
+            items,
+            multi,
+            query: String::new(),
+            filtered: Vec::new(),
+            cursor: 0,
+            scroll: 0,
+            selected: BTreeSet::new(),
+        };
+        state.filter();
+        state
+    }
+
+    fn filter(&mut self) {
+        let mut scored: Vec<(i64, usize)> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| fuzzy_score(&self.query, item).map(|score| (score, i)))
+            .collect();
+        // the sort is stable, so an empty query keeps the order of the items
+        if !self.query.is_empty() {
+            scored.sort_by(|(a, _), (b, _)| b.cmp(a));
+        }
+        self.filtered = scored.into_iter().map(|(_, i)| i).collect();
+        self.cursor = 0;
+        self.scroll = 0;
+    }
+
+    fn current(&self) -> Option<usize> {
+        self.filtered.get(self.cursor).copied()
+    }
+
+    fn move_cursor(&mut self, down: bool) {
+        if down && self.cursor + 1 < self.filtered.len() {
+            self.cursor += 1;
+        } else if !down {
+            self.cursor = self.cursor.saturating_sub(1);
+        }
+    }
+
+    /// Keeps the cursor inside the `height` visible rows.
+    fn scroll_to_cursor(&mut self, height: usize) {
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if height > 0 && self.cursor >= self.scroll + height {
+            self.scroll = self.cursor + 1 - height;
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Action {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Action::Cancel,
+            KeyCode::Char('c' | 'g') if control => return Action::Cancel,
+            KeyCode::Enter => return Action::Accept,
+            KeyCode::Up => self.move_cursor(false),
+            KeyCode::Char('p' | 'k') if control => self.move_cursor(false),
+            KeyCode::Down => self.move_cursor(true),
+            KeyCode::Char('n' | 'j') if control => self.move_cursor(true),
+            KeyCode::Tab if self.multi => {
+                if let Some(current) = self.current() {
+                    if !self.selected.remove(&current) {
+                        self.selected.insert(current);
+                    }
+                    self.move_cursor(true);
+                }
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.filter();
+            }
+            KeyCode::Char('u') if control => {
+                self.query.clear();
+                self.filter();
+            }
+            KeyCode::Char(c) if !control => {
+                self.query.push(c);
+                self.filter();
+            }
+            _ => {}
+        }
+        Action::Continue
+    }
+
+    /// The selected items, or the item under the cursor if none is selected.
+    fn result(&self) -> Vec<String> {
+        let indices: Vec<usize> = if self.selected.is_empty() {
+            self.current().into_iter().collect()
+        } else {
+            self.selected.iter().copied().collect()
+        };
+        indices
+            .into_iter()
+            .filter_map(|i| self.items.get(i).cloned())
+            .collect()
+    }
+}
+
+/// Shortens `line` to `width` characters and replaces characters which would
+/// break the layout.
+fn fit_line(line: &str, width: usize) -> String {
+    line.replace('\t', &" ".repeat(TAB_WIDTH))
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(width)
+        .collect()
+}
+
+fn draw(
+    terminal: &mut impl Write,
+    state: &mut PickerState,
+    preview: &impl Fn(&str) -> Vec<String>,
+) -> Result<()> {
+    let (columns, rows) = terminal::size()?;
+    let width = usize::from(columns);
+    let rows = usize::from(rows);
+    // prompt and counter on top, the rest is split between list and preview
+    let list_height = rows.saturating_sub(2) / 2;
+    let preview_height = rows.saturating_sub(3 + list_height);
+    state.scroll_to_cursor(list_height);
+
+    let mut lines = vec![
+        fit_line(&format!("{PROMPT}{}", state.query), width),
+        fit_line(
+            &format!("  {}/{}", state.filtered.len(), state.items.len()),
+            width,
+        ),
+    ];
+    for position in state.scroll..state.scroll + list_height {
+        let Some(item) = state
+            .filtered
+            .get(position)
+            .and_then(|i| state.items.get(*i))
+        else {
+            lines.push(String::new());
+            continue;
+        };
+        let is_selected = state
+            .filtered
+            .get(position)
+            .is_some_and(|i| state.selected.contains(i));
+        let marker = if is_selected {
+            SELECTED_MARKER
+        } else {
+            UNSELECTED_MARKER
+        };
+        lines.push(fit_line(&format!("{marker}{item}"), width));
+    }
+    lines.push("─".repeat(width));
+    if let Some(item) = state.current().and_then(|i| state.items.get(i)) {
+        for line in preview(item).iter().take(preview_height) {
+            lines.push(fit_line(line, width));
+        }
+    }
+
+    let cursor_row = 2 + state.cursor - state.scroll;
+    for (row, line) in lines.iter().enumerate().take(rows) {
+        let row = u16::try_from(row)?;
+        queue!(
+            terminal,
+            cursor::MoveTo(0, row),
+            Clear(ClearType::CurrentLine)
+        )?;
+        if usize::from(row) == cursor_row && state.current().is_some() {
+            queue!(
+                terminal,
+                SetAttribute(Attribute::Reverse),
+                Print(line),
+                SetAttribute(Attribute::Reset)
+            )?;
+        } else {
+            queue!(terminal, Print(line))?;
+        }
+    }
+    queue!(terminal, Clear(ClearType::FromCursorDown))?;
+    terminal.flush()?;
+    Ok(())
+}
+
+fn run(
+    terminal: &mut impl Write,
+    state: &mut PickerState,
+    preview: &impl Fn(&str) -> Vec<String>,
+) -> Result<Vec<String>> {
+    loop {
+        draw(terminal, state, preview)?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind == KeyEventKind::Release {
+            continue;
+        }
+        match state.handle_key(key) {
+            Action::Continue => {}
+            Action::Accept => return Ok(state.result()),
+            Action::Cancel => return Ok(Vec::new()),
+        }
+    }
+}
+
+/// Lets the user pick one (or with `multi` several) of `items` in the
+/// terminal, `preview` provides the lines shown for the item under the
+/// cursor. Returns no items if the user cancels.
+///
+/// The picker is drawn on stderr, so stdout can still be redirected.
+pub fn pick(
+    items: &[String],
+    multi: bool,
+    preview: impl Fn(&str) -> Vec<String>,
+) -> Result<Vec<String>> {
+    let mut state = PickerState::new(items, multi);
+    let mut terminal = io::stderr();
+    terminal::enable_raw_mode()?;
+    execute!(terminal, EnterAlternateScreen, cursor::Hide)?;
+    let result = run(&mut terminal, &mut state, &preview);
+    // restore the terminal even if the picker failed
+    let restored = execute!(terminal, cursor::Show, LeaveAlternateScreen);
+    terminal::disable_raw_mode()?;
+    restored?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items() -> Vec<String> {
+        ["todo", "projects/api", "projects/web"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn type_query(state: &mut PickerState, query: &str) {
+        for c in query.chars() {
+            assert_eq!(state.handle_key(key(KeyCode::Char(c))), Action::Continue);
+        }
+    }
+
+    #[test]
+    fn test_filter() {
+        let items = items();
+        let mut state = PickerState::new(&items, false);
+        assert_eq!(state.filtered, [0, 1, 2]);
+
+        type_query(&mut state, "web");
+        assert_eq!(state.filtered, [2]);
+
+        state.handle_key(key(KeyCode::Backspace));
+        state.handle_key(key(KeyCode::Backspace));
+        assert_eq!(state.query, "w");
+        assert_eq!(state.filtered, [2]);
+
+        state.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        assert_eq!(state.filtered, [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_navigation() {
+        let items = items();
+        let mut state = PickerState::new(&items, false);
+        state.handle_key(key(KeyCode::Up));
+        assert_eq!(state.result(), ["todo"]);
+        state.handle_key(key(KeyCode::Down));
+        state.handle_key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL));
+        state.handle_key(key(KeyCode::Down));
+        assert_eq!(state.result(), ["projects/web"]);
+        assert_eq!(state.handle_key(key(KeyCode::Enter)), Action::Accept);
+    }
+
+    #[test]
+    fn test_scroll() {
+        let items = items();
+        let mut state = PickerState::new(&items, false);
+        state.handle_key(key(KeyCode::Down));
+        state.handle_key(key(KeyCode::Down));
+        state.scroll_to_cursor(2);
+        assert_eq!(state.scroll, 1);
+        state.handle_key(key(KeyCode::Up));
+        state.handle_key(key(KeyCode::Up));
+        state.scroll_to_cursor(2);
+        assert_eq!(state.scroll, 0);
+    }
+
+    #[test]
+    fn test_multi_select() {
+        let items = items();
+        let mut state = PickerState::new(&items, true);
+        state.handle_key(key(KeyCode::Tab));
+        state.handle_key(key(KeyCode::Down));
+        state.handle_key(key(KeyCode::Tab));
+        assert_eq!(state.result(), ["todo", "projects/web"]);
+
+        let mut state = PickerState::new(&items, false);
+        state.handle_key(key(KeyCode::Tab));
+        assert_eq!(state.result(), ["todo"]);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let items = items();
+        let mut state = PickerState::new(&items, false);
+        assert_eq!(state.handle_key(key(KeyCode::Esc)), Action::Cancel);
+        assert_eq!(
+            state.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Action::Cancel
+        );
+    }
+
+    #[test]
+    fn test_fit_line() {
+        assert_eq!(fit_line("a\tb\x07c", 20), "a    bc");
+        assert_eq!(fit_line("abcdef", 3), "abc");
+    }
+}
@@ -0,0 +1,217 @@
+/// Maximum number of suggestions shown for an ambiguous name.
+const MAX_SUGGESTIONS: usize = 5;
+
+const MATCH_SCORE: i64 = 1;
+const CONSECUTIVE_BONUS: i64 = 5;
+const BOUNDARY_BONUS: i64 = 3;
+const GAP_PENALTY: i64 = 1;
+
+#[derive(Debug, PartialEq)]
+pub enum Resolved {
+    Found(String),
+    /// Best matches first.
+    Ambiguous(Vec<String>),
+    NotFound,
+}
+
+fn is_boundary(c: char) -> bool {
+    matches!(c, '/' | '_' | '-' | '.' | ' ')
+}
+
+/// Scores `name` if all characters of `query` appear in it in order, ignoring
+/// case. Consecutive characters and characters at the start of a word score
+/// higher, skipped characters lower.
+pub fn fuzzy_score(query: &str, name: &str) -> Option<i64> {
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous_match: Option<usize> = None;
+    for query_char in query.to_lowercase().chars() {
+        let offset = name
+            .get(position..)?
+            .iter()
+            .position(|c| *c == query_char)?;
+        let index = position + offset;
+        score += MATCH_SCORE;
+        if previous_match.is_some_and(|previous| previous + 1 == index) {
+            score += CONSECUTIVE_BONUS;
+        }
+        if index == 0 || name.get(index - 1).copied().is_some_and(is_boundary) {
+            score += BOUNDARY_BONUS;
+        }
+        #[allow(clippy::cast_possible_wrap)] // note names are short
+        let gap = offset as i64;
+        score -= gap * GAP_PENALTY;
+        previous_match = Some(index);
+        position = index + 1;
+    }
+    Some(score)
+}
+
+fn ambiguous_or_found(mut matches: Vec<String>) -> Resolved {
+    if matches.len() == 1 {
+        return matches.pop().map_or(Resolved::NotFound, Resolved::Found);
+    }
+    matches.truncate(MAX_SUGGESTIONS);
+    Resolved::Ambiguous(matches)
+}
+
+/// Resolves `query` to one of `names`, trying an exact match, a unique prefix
+/// and a unique fuzzy match in this order.
+pub fn resolve(query: &str, names: &[String]) -> Resolved {
+    if names.iter().any(|name| name == query) {
+        return Resolved::Found(query.to_owned());
+    }
+
+    let mut prefix_matches: Vec<String> = names
+        .iter()
+        .filter(|name| name.starts_with(query))
+        .cloned()
+        .collect();
+    if !prefix_matches.is_empty() {
+        prefix_matches.sort_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+        return ambiguous_or_found(prefix_matches);
+    }
+
+    let mut fuzzy_matches: Vec<(i64, &String)> = names
+        .iter()
+        .filter_map(|name| fuzzy_score(query, name).map(|score| (score, name)))
+        .collect();
+    if fuzzy_matches.is_empty() {
+        return Resolved::NotFound;
+    }
+    fuzzy_matches.sort_by(|(score_a, a), (score_b, b)| {
+        score_b
+            .cmp(score_a)
+            .then(a.len().cmp(&b.len()))
+            .then(a.cmp(b))
+    });
+    ambiguous_or_found(
+        fuzzy_matches
+            .into_iter()
+            .map(|(_, name)| name.clone())
+            .collect(),
+    )
+}
+
+/// Whether the whole `name` matches the glob `pattern`, where `*` matches any
+/// text, including `/`, and `?` a single character.
+pub fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    // position in the pattern after the last `*` and the name position it
+    // was tried at, to backtrack to
+    let mut backtrack = None;
+    let (mut p, mut n) = (0, 0);
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || Some(&c) == name.get(n) => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_n)) => {
+                    backtrack = Some((star_p, star_n + 1));
+                    p = star_p;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern
+        .get(p..)
+        .is_some_and(|rest| rest.iter().all(|&c| c == '*'))
+}
+
+#[cfg(test)]
+#[allow(clippy::panic)] // tests
+mod tests {
+    use super::*;
+
+    fn names() -> Vec<String> {
+        [
+            "projects/api/design",
+            "projects/api/notes",
+            "projects/web",
+            "todo",
+            "todo_old",
+            "meeting-2026",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    #[test]
+    fn test_exact() {
+        assert_eq!(resolve("todo", &names()), Resolved::Found("todo".into()));
+    }
+
+    #[test]
+    fn test_unique_prefix() {
+        assert_eq!(
+            resolve("projects/w", &names()),
+            Resolved::Found("projects/web".into())
+        );
+        assert_eq!(
+            resolve("mee", &names()),
+            Resolved::Found("meeting-2026".into())
+        );
+    }
+
+    #[test]
+    fn test_ambiguous_prefix() {
+        assert_eq!(
+            resolve("projects/api", &names()),
+            Resolved::Ambiguous(vec![
+                "projects/api/notes".into(),
+                "projects/api/design".into(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_fuzzy() {
+        assert_eq!(
+            resolve("design", &names()),
+            Resolved::Found("projects/api/design".into())
+        );
+        assert_eq!(
+            resolve("ToDoOld", &names()),
+            Resolved::Found("todo_old".into())
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_ranking() {
+        let Resolved::Ambiguous(suggestions) = resolve("pan", &names()) else {
+            panic!()
+        };
+        assert_eq!(
+            suggestions.first().map(String::as_str),
+            Some("projects/api/notes")
+        );
+    }
+
+    #[test]
+    fn test_not_found() {
+        assert_eq!(resolve("xyz", &names()), Resolved::NotFound);
+        assert_eq!(resolve("todo", &[]), Resolved::NotFound);
+    }
+
+    #[test]
+    fn test_glob() {
+        assert!(glob_matches("meeting-*", "meeting-2026-03"));
+        assert!(glob_matches("*/design", "projects/api/design"));
+        assert!(glob_matches("to?o", "todo"));
+        assert!(glob_matches("*a*b*", "xaxxbx"));
+        assert!(glob_matches("*", ""));
+        assert!(!glob_matches("meeting-*", "work/meeting-1"));
+        assert!(!glob_matches("to?o", "too"));
+        assert!(!glob_matches("*a*b", "xaxxbx"));
+    }
+}
@@ -0,0 +1,144 @@
+use std::ops::Range;
+
+use anyhow::Result;
+use regex::{Regex, RegexBuilder};
+use serde::{Serialize, Serializer};
+
+use crate::error::AppError;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SearchOptions {
+    pub ignore_case: bool,
+    pub whole_word: bool,
+    pub regex: bool,
+}
+
+/// A line of a note matching the search query.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchMatch {
+    pub note: String,
+    pub line_number: usize,
+    pub line: String,
+    /// Byte ranges of the matches inside `line`.
+    #[serde(serialize_with = "serialize_ranges")]
+    pub ranges: Vec<Range<usize>>,
+}
+
+/// Ranges are serialized as `[start, end]` pairs.
+fn serialize_ranges<S: Serializer>(
+    ranges: &[Range<usize>],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(ranges.iter().map(|range| [range.start, range.end]))
+}
+
+pub struct Matcher {
+    regex: Regex,
+}
+
+impl Matcher {
+    pub fn new(query: &str, options: SearchOptions) -> Result<Self> {
+        let mut pattern = if options.regex {
+            query.to_owned()
+        } else {
+            regex::escape(query)
+        };
+        if options.whole_word {
+            pattern = format!(r"\b(?:{pattern})\b");
+        }
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(options.ignore_case)
+            .build()
+            .map_err(|e| AppError::InvalidSearchPattern(e.to_string()))?;
+        Ok(Self { regex })
+    }
+
+    /// Returns every matching line of `content`, line numbers start at 1.
+    pub fn find_matches(&self, note: &str, content: &str) -> Vec<SearchMatch> {
+        content
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let ranges: Vec<Range<usize>> = self
+                    .regex
+                    .find_iter(line)
+                    .filter(|m| !m.is_empty())
+                    .map(|m| m.range())
+                    .collect();
+                if ranges.is_empty() {
+                    return None;
+                }
+                Some(SearchMatch {
+                    note: note.to_owned(),
+                    line_number: i + 1,
+                    line: line.to_owned(),
+                    ranges,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // tests
+mod tests {
+    use super::*;
+
+    const CONTENT: &str = "Meeting notes\nthe meeting was long\nmeetings are fun\n";
+
+    fn lines(matcher: &Matcher) -> Vec<usize> {
+        matcher
+            .find_matches("note", CONTENT)
+            .iter()
+            .map(|m| m.line_number)
+            .collect()
+    }
+
+    #[test]
+    fn test_plain() {
+        let matcher = Matcher::new("meeting", SearchOptions::default()).unwrap();
+        assert_eq!(lines(&matcher), [2, 3]);
+        let results = matcher.find_matches("note", CONTENT);
+        assert_eq!(
+            results.first().unwrap().ranges,
+            vec![Range { start: 4, end: 11 }]
+        );
+    }
+
+    #[test]
+    fn test_plain_escapes_regex() {
+        let matcher = Matcher::new("notes.", SearchOptions::default()).unwrap();
+        assert!(lines(&matcher).is_empty());
+    }
+
+    #[test]
+    fn test_ignore_case() {
+        let options = SearchOptions {
+            ignore_case: true,
+            ..SearchOptions::default()
+        };
+        let matcher = Matcher::new("MEETING", options).unwrap();
+        assert_eq!(lines(&matcher), [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_whole_word() {
+        let options = SearchOptions {
+            whole_word: true,
+            ..SearchOptions::default()
+        };
+        let matcher = Matcher::new("meeting", options).unwrap();
+        assert_eq!(lines(&matcher), [2]);
+    }
+
+    #[test]
+    fn test_regex() {
+        let options = SearchOptions {
+            regex: true,
+            ..SearchOptions::default()
+        };
+        let matcher = Matcher::new("^(the|meetings) ", options).unwrap();
+        assert_eq!(lines(&matcher), [2, 3]);
+        assert!(Matcher::new("(", options).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::BTreeSet;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::front_matter::{FieldValue, FrontMatter};
+
+/// Front matter field holding the tags of a note.
+pub const TAGS: &str = "tags";
+
+/// Inline `#tag` at the start of a line, after whitespace or a `(`.
+#[allow(clippy::expect_used)] // constant pattern
+static INLINE_TAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|[\s(])#([\w/-]+)").expect("valid inline tag pattern"));
+
+/// Tags may contain letters, digits, `_`, `-` and `/` for nested tags.
+pub fn is_valid_tag(tag: &str) -> bool {
+    !tag.is_empty()
+        && tag
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '/'))
+        && !tag.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Tags listed in the `tags` field, a text value may separate them with
+/// commas or whitespace.
+pub fn front_matter_tags(front_matter: &FrontMatter) -> Vec<String> {
+    match front_matter.get(TAGS) {
+        Some(FieldValue::List(tags)) => tags,
+        Some(FieldValue::Text(text)) => text
+            .split([',', ' '])
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_owned)
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Tags of the front matter and inline `#tags` of the text. Headings like
+/// `# Title` aren't tags, neither are numbers like `#42`.
+pub fn note_tags(content: &str) -> BTreeSet<String> {
+    let (mut tags, body) = match FrontMatter::parse(content) {
+        Some((front_matter, body)) => {
+            (front_matter_tags(&front_matter).into_iter().collect(), body)
+        }
+        None => (BTreeSet::new(), content),
+    };
+    tags.extend(
+        INLINE_TAG
+            .captures_iter(body)
+            .filter_map(|captures| captures.get(1))
+            .map(|tag| tag.as_str())
+            .filter(|tag| is_valid_tag(tag))
+            .map(str::to_owned),
+    );
+    tags
+}
+
+/// Filter of `--tag` arguments. All arguments have to match, an argument
+/// matches if any of its comma separated tags matches and `!tag` matches
+/// notes without the tag.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagFilter {
+    /// Alternatives of every argument, with whether they're negated.
+    clauses: Vec<Vec<(String, bool)>>,
+}
+
+impl TagFilter {
+    /// Returns the invalid tag if an argument contains one.
+    pub fn parse(arguments: &[String]) -> Result<Self, String> {
+        let clauses = arguments
+            .iter()
+            .map(|argument| {
+                argument
+                    .split(',')
+                    .map(|tag| {
+                        let (tag, negated) = match tag.strip_prefix('!') {
+                            Some(tag) => (tag, true),
+                            None => (tag, false),
+                        };
+                        if is_valid_tag(tag) {
+                            Ok((tag.to_owned(), negated))
+                        } else {
+                            Err(tag.to_owned())
+                        }
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { clauses })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+
+    pub fn matches(&self, tags: &BTreeSet<String>) -> bool {
+        self.clauses.iter().all(|alternatives| {
+            alternatives
+                .iter()
+                .any(|(tag, negated)| tags.contains(tag) != *negated)
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // tests
+mod tests {
+    use super::*;
+
+    fn tags(tags: &[&str]) -> BTreeSet<String> {
+        tags.iter().map(|&tag| tag.to_owned()).collect()
+    }
+
+    #[test]
+    fn test_note_tags() {
+        let content =
+            "---\ntags: [work, api]\n---\n# Design #draft\nSee #42 and page#anchor\n(#work/q3)\n";
+        assert_eq!(
+            note_tags(content),
+            tags(&["api", "draft", "work", "work/q3"])
+        );
+        assert_eq!(note_tags("+++\ntags = \"a, b\"\n+++\n"), tags(&["a", "b"]));
+        assert_eq!(note_tags("## Heading\n#todo"), tags(&["todo"]));
+    }
+
+    #[test]
+    fn test_filter() {
+        let filter = TagFilter::parse(&["work,home".into(), "!done".into()]).unwrap();
+        assert!(filter.matches(&tags(&["work"])));
+        assert!(filter.matches(&tags(&["home", "api"])));
+        assert!(!filter.matches(&tags(&["work", "done"])));
+        assert!(!filter.matches(&tags(&["api"])));
+        assert!(TagFilter::parse(&[]).unwrap().matches(&tags(&[])));
+
+        assert_eq!(TagFilter::parse(&["a,b c".into()]), Err("b c".into()));
+        assert_eq!(TagFilter::parse(&["!".into()]), Err(String::new()));
+    }
+}
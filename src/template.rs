@@ -0,0 +1,77 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+
+use crate::error::AppError;
+
+const OPEN: &str = "{{";
+const CLOSE: &str = "}}";
+
+fn is_variable_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Replaces every `{{variable}}` of `template` by its value, whitespace
+/// around the name is ignored. Braces around anything which isn't a variable
+/// name are kept as they are.
+pub fn render(template: &str, variables: &BTreeMap<String, String>) -> Result<String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some((before, after_open)) = rest.split_once(OPEN) {
+        rendered.push_str(before);
+        let Some((inner, after_close)) = after_open.split_once(CLOSE) else {
+            rendered.push_str(OPEN);
+            rest = after_open;
+            break;
+        };
+        let name = inner.trim();
+        if is_variable_name(name) {
+            let value = variables
+                .get(name)
+                .ok_or_else(|| AppError::UnknownTemplateVariable(name.to_owned()))?;
+            rendered.push_str(value);
+        } else {
+            rendered.push_str(OPEN);
+            rendered.push_str(inner);
+            rendered.push_str(CLOSE);
+        }
+        rest = after_close;
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // tests
+mod tests {
+    use super::*;
+
+    fn variables() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("name".into(), "standup".into()),
+            ("date".into(), "2026-01-02".into()),
+        ])
+    }
+
+    #[test]
+    fn test_render() {
+        let rendered = render("# {{name}}\nDate: {{ date }}\n", &variables()).unwrap();
+        assert_eq!(rendered, "# standup\nDate: 2026-01-02\n");
+    }
+
+    #[test]
+    fn test_render_keeps_other_braces() {
+        let template = "fn f() {{ x }}\n{{ a + b }} {{ name";
+        assert!(render(template, &variables()).is_err());
+        let template = "{{ a + b }} {{}} {{ name";
+        assert_eq!(render(template, &variables()).unwrap(), template);
+    }
+
+    #[test]
+    fn test_unknown_variable() {
+        assert!(render("{{attendees}}", &variables()).is_err());
+    }
+}
@@ -0,0 +1,83 @@
+use anyhow::Result;
+use std::io::{IsTerminal, Read, Write};
+use std::process::{Command, Stdio};
+
+use crate::error::SystemError;
+use crate::file_operations::check_command;
+use crate::picker;
+
+/// Number of lines of a note shown in the preview of the picker.
+const PREVIEW_LINES: usize = 200;
+
+pub trait TerminalOperations {
+    fn read_stdin(&mut self) -> Result<String>;
+
+    /// Lets the user pick some of `names` interactively, `preview` returns
+    /// the content shown for a name.
+    fn pick(
+        &mut self,
+        names: &[String],
+        multi: bool,
+        preview: impl Fn(&str) -> String,
+    ) -> Result<Vec<String>>;
+
+    /// Shows `content` in the pager if stdout is a terminal. Returns whether
+    /// it was paged, otherwise the caller has to print it.
+    fn page(&mut self, pager_command: &str, content: &str) -> Result<bool>;
+
+    /// Whether output may be colored, stdout is a terminal and `NO_COLOR`
+    /// isn't set.
+    fn use_color(&self) -> bool;
+}
+
+pub struct Terminal;
+impl TerminalOperations for Terminal {
+    fn read_stdin(&mut self) -> Result<String> {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input)?;
+        Ok(input)
+    }
+
+    fn pick(
+        &mut self,
+        names: &[String],
+        multi: bool,
+        preview: impl Fn(&str) -> String,
+    ) -> Result<Vec<String>> {
+        if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+            return Err(SystemError::NoTerminal.into());
+        }
+        picker::pick(names, multi, |name| {
+            preview(name)
+                .lines()
+                .take(PREVIEW_LINES)
+                .map(str::to_owned)
+                .collect()
+        })
+    }
+
+    fn page(&mut self, pager_command: &str, content: &str) -> Result<bool> {
+        if !std::io::stdout().is_terminal() {
+            return Ok(false);
+        }
+        let mut args = pager_command.split_whitespace();
+        let Some(program) = args.next() else {
+            return Ok(false);
+        };
+        check_command(program)?;
+        let mut pager = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = pager.stdin.take() {
+            // the pager may be quit before it read everything
+            stdin.write_all(content.as_bytes()).ok();
+        }
+        pager.wait()?;
+        Ok(true)
+    }
+
+    fn use_color(&self) -> bool {
+        std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+    }
+}
@@ -0,0 +1,113 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// A note in the trash, its content is stored in the trash directory in a
+/// file named after `id`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TrashEntry {
+    pub id: String,
+    pub notebook: String,
+    pub name: String,
+    pub archived: bool,
+    /// Deletion time in seconds since the unix epoch.
+    pub deleted: i64,
+}
+
+/// Manifest of the trash, keeps where the trashed notes came from.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Trash {
+    pub entries: Vec<TrashEntry>,
+}
+
+impl Trash {
+    /// Id for a note deleted at `time` which isn't used yet.
+    pub fn new_id(&self, time: DateTime<Local>) -> String {
+        let base = time.format("%Y%m%d-%H%M%S").to_string();
+        let is_used = |id: &str| self.entries.iter().any(|entry| entry.id == id);
+        if !is_used(&base) {
+            return base;
+        }
+        // one of these is free, as there are fewer entries
+        (1..=self.entries.len())
+            .map(|n| format!("{base}-{n}"))
+            .find(|id| !is_used(id))
+            .unwrap_or(base)
+    }
+
+    /// Position of the entry with the id `query`, or of the last deleted
+    /// note with the name `query`.
+    pub fn find(&self, query: &str) -> Option<usize> {
+        if let Some(position) = self.entries.iter().position(|entry| entry.id == query) {
+            return Some(position);
+        }
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.name == query)
+            .max_by_key(|(_, entry)| entry.deleted)
+            .map(|(position, _)| position)
+    }
+
+    /// Removes the entries deleted before or at `cutoff` and returns them, so
+    /// an age of zero takes every entry.
+    pub fn take_older_than(&mut self, cutoff: i64) -> Vec<TrashEntry> {
+        let (older, newer) = std::mem::take(&mut self.entries)
+            .into_iter()
+            .partition(|entry| entry.deleted <= cutoff);
+        self.entries = newer;
+        older
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)] // tests
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn entry(id: &str, name: &str, deleted: i64) -> TrashEntry {
+        TrashEntry {
+            id: id.into(),
+            notebook: "default".into(),
+            name: name.into(),
+            archived: false,
+            deleted,
+        }
+    }
+
+    #[test]
+    fn test_new_id() {
+        let time = Local.with_ymd_and_hms(2026, 10, 18, 9, 30, 0).unwrap();
+        let mut trash = Trash::default();
+        assert_eq!(trash.new_id(time), "20261018-093000");
+        trash.entries.push(entry("20261018-093000", "a", 0));
+        trash.entries.push(entry("20261018-093000-1", "b", 0));
+        assert_eq!(trash.new_id(time), "20261018-093000-2");
+    }
+
+    #[test]
+    fn test_find() {
+        let trash = Trash {
+            entries: vec![
+                entry("1", "todo", 10),
+                entry("2", "todo", 20),
+                entry("3", "1", 5),
+            ],
+        };
+        assert_eq!(trash.find("1"), Some(0));
+        assert_eq!(trash.find("todo"), Some(1));
+        assert_eq!(trash.find("missing"), None);
+    }
+
+    #[test]
+    fn test_take_older_than() {
+        let mut trash = Trash {
+            entries: vec![entry("1", "a", 10), entry("2", "b", 20)],
+        };
+        let older = trash.take_older_than(15);
+        assert_eq!(older, [entry("1", "a", 10)]);
+        assert_eq!(trash.entries, [entry("2", "b", 20)]);
+        assert_eq!(trash.take_older_than(20), [entry("2", "b", 20)]);
+    }
+}
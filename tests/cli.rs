@@ -0,0 +1,105 @@
+#![allow(clippy::unwrap_used)] // tests
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// An rn root and home directory in the temp directory, removed on drop.
+struct TestEnv {
+    dir: PathBuf,
+}
+
+impl TestEnv {
+    fn new() -> Self {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "rn-cli-test-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(dir.join("home")).unwrap();
+        Self { dir }
+    }
+
+    fn rn(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_rn"))
+            .args(args)
+            .env("RN_ROOT", self.dir.join("root"))
+            .env("HOME", self.dir.join("home"))
+            .env_remove("EDITOR")
+            .output()
+            .unwrap()
+    }
+}
+
+impl Drop for TestEnv {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn test_success() {
+    let env = TestEnv::new();
+    let output = env.rn(&["new", "todo", "-m", "milk"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "Created note\n");
+    assert!(stderr(&output).is_empty());
+
+    let output = env.rn(&["show", "todo"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "milk\n");
+}
+
+#[test]
+fn test_errors_go_to_stderr() {
+    let env = TestEnv::new();
+    let output = env.rn(&["show", "todo"]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stdout(&output).is_empty());
+    assert_eq!(stderr(&output), "No note named \"todo\" exists.\n");
+
+    let output = env.rn(&["--format", "json", "show", "todo"]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stdout(&output).is_empty());
+    assert!(stderr(&output).starts_with("{\"type\":\"error\",\"kind\":\"not_found\""));
+}
+
+#[test]
+fn test_exit_codes() {
+    let env = TestEnv::new();
+    assert_eq!(env.rn(&["new", "todo"]).status.code(), Some(0));
+    // already exists
+    assert_eq!(env.rn(&["new", "todo"]).status.code(), Some(4));
+    // usage errors of clap and rn
+    assert_eq!(env.rn(&["new"]).status.code(), Some(2));
+    assert_eq!(env.rn(&["new", "a//b"]).status.code(), Some(2));
+    // missing and ambiguous notes
+    assert_eq!(env.rn(&["show", "missing"]).status.code(), Some(3));
+    assert_eq!(env.rn(&["new", "work/plan"]).status.code(), Some(0));
+    assert_eq!(env.rn(&["new", "home/plan"]).status.code(), Some(0));
+    assert_eq!(env.rn(&["show", "plan"]).status.code(), Some(3));
+    // other errors
+    assert_eq!(env.rn(&["redo"]).status.code(), Some(1));
+}
+
+#[test]
+fn test_quiet() {
+    let env = TestEnv::new();
+    let output = env.rn(&["new", "todo", "--quiet"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).is_empty());
+
+    let output = env.rn(&["-q", "new", "todo"]);
+    assert_eq!(output.status.code(), Some(4));
+    assert!(stdout(&output).is_empty());
+    assert!(!stderr(&output).is_empty());
+}